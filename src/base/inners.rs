@@ -1,6 +1,10 @@
 use std::hash::Hash;
 use std::num::TryFromIntError;
 
+use crate::errors::{CONVERT_FROM_USIZE_ERROR, RANDOM_ID_ERROR};
+
+const DEFAULT_OUT_OF_IDS_MSG: &str = "Ids: ran out of ids";
+
 /**
 [IdInner] is a type which wraps an [IdImpl] and provides it with an error message.
 
@@ -29,6 +33,16 @@ impl <'a, T: IdImpl> IdInner<'a, T> {
     }
 }
 
+impl <'a, T: RandomIdImpl> IdInner<'a, T> {
+    /// Get a random id, and give it an error message for all future ids to use
+    pub fn random(out_of_ids_msg: &'a str) -> Self {
+        match T::random() {
+            Ok(inner_type) => Self { inner_type, out_of_ids_msg },
+            Err(()) => panic!("{}", RANDOM_ID_ERROR)
+        }
+    }
+}
+
 impl <'a, T: IdImpl> TryInto<usize> for IdInner<'a,T> {
     type Error = ();
     fn try_into(self) -> Result<usize, Self::Error> {
@@ -40,7 +54,12 @@ impl <'a, T: IdImpl> TryInto<usize> for IdInner<'a,T> {
 }
 
 impl <'a, T: IdImpl> From<usize> for IdInner<'a,T> {
-    fn from(val: usize) -> Self { val.into() }
+    fn from(val: usize) -> Self {
+        Self {
+            inner_type: T::try_from(val).expect(CONVERT_FROM_USIZE_ERROR),
+            out_of_ids_msg: DEFAULT_OUT_OF_IDS_MSG
+        }
+    }
 }
 
 pub trait IdImpl: Clone + Copy + PartialEq + Eq + Hash + 
@@ -50,3 +69,13 @@ pub trait IdImpl: Clone + Copy + PartialEq + Eq + Hash +
     /// Get the next id, after this one
     fn next(&self) -> Result<Self,()>;
 }
+
+/**
+An [IdImpl] whose values can also be drawn at random.
+
+Implementations should use a cryptographically secure source of randomness, so that ids cannot be guessed or enumerated.
+ */
+pub trait RandomIdImpl: IdImpl {
+    /// Get a uniformly random id of this type, returning an [Err] if no randomness was available
+    fn random() -> Result<Self,()>;
+}
@@ -15,6 +15,51 @@ pub trait Identifier: Clone + Copy + PartialEq + Eq + Hash + Debug
     fn next(self) -> Self;
 }
 
+/**
+An [Identifier] which can also be drawn at random, rather than only in sequence.
+
+This is intended for ids which are exposed publicly, and so must not be enumerable.
+Any [Identifier] backed by a [crate::RandomIdImpl] can implement it, and then be inserted into a tracker with `put_random`:
+```
+use std::num::TryFromIntError;
+use ids::{Id64, IdImpl, Identifier, IdentifiedBy, RandomIdImpl, RandomIdentifier};
+use ids::trackers::{IdTracker, SparseIdTracker};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct TokenId(Id64);
+impl Identifier for TokenId {
+    fn first() -> Self { Self(Id64::first()) }
+    fn next(self) -> Self { Self(self.0.next().expect("Ran out of token ids")) }
+}
+impl RandomIdentifier for TokenId {
+    fn random() -> Self { Self(Id64::random().expect("No randomness was available")) }
+}
+impl TryFrom<usize> for TokenId {
+    type Error = TryFromIntError;
+    fn try_from(value: usize) -> Result<Self, Self::Error> { Id64::try_from(value).map(Self) }
+}
+impl TryFrom<TokenId> for usize {
+    type Error = TryFromIntError;
+    fn try_from(value: TokenId) -> Result<Self, Self::Error> { usize::try_from(value.0) }
+}
+
+struct Token { id: TokenId }
+impl IdentifiedBy<TokenId> for Token {
+    fn get_id(&self) -> TokenId { self.id }
+    fn set_id(&mut self, id: TokenId) { self.id = id }
+}
+
+let mut tokens = SparseIdTracker::default();
+let token = tokens.put_random(Token { id: TokenId::first() }).unwrap();
+let id = token.lock().unwrap().get_id();
+assert!(tokens.get(id).is_some());
+```
+ */
+pub trait RandomIdentifier: Identifier {
+    /// Get a random id of this type, panicking if no randomness was available
+    fn random() -> Self;
+}
+
 /**
 An object which can be identified purely from an [Identifier]
 
@@ -32,6 +77,17 @@ pub trait IdentifiedBy<T: Identifier> {
     fn set_id(&mut self,id: T);
 }
 
+/**
+Boxed objects are identified by whatever they contain, including unsized trait objects.
+
+This allows trackers to store `Box<dyn Trait>` elements, where `Trait` has [IdentifiedBy] as a supertrait.
+Setting the id of the box sets the id of the object inside it, through its vtable if it is a trait object.
+ */
+impl <I: Identifier, T: IdentifiedBy<I> + ?Sized> IdentifiedBy<I> for Box<T> {
+    fn get_id(&self) -> I { (**self).get_id() }
+    fn set_id(&mut self, id: I) { (**self).set_id(id) }
+}
+
 impl <T: Identifier> Eq for dyn IdentifiedBy<T> {}
 impl <T: Identifier> PartialEq for dyn IdentifiedBy<T> {
     fn eq(&self, other: &Self) -> bool
@@ -41,3 +97,96 @@ impl <T: Identifier> Hash for dyn IdentifiedBy<T> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H)
         { self.get_id().hash(state) }
 }
+
+/**
+A wrapper which stores an id alongside a value, so that any type can be identified, including foreign types which have no id of their own.
+
+Trackers update the wrapper's id, so the value itself is never changed.
+Equality compares both the id and the value, so two elements of a tracker are never equal, even when their values are.
+Hashing only looks at the value, which is still consistent with equality, so that a [crate::trackers::ContentIdTracker]
+finds equal values under any id; it gives the new wrapper each candidate's id before comparing them.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Identified<I: Identifier, T> {
+    id: I,
+    value: T,
+}
+
+impl <I: Identifier, T> Identified<I,T> {
+    /// Wrap a value with a placeholder id, which will be replaced when it is inserted into a tracker
+    pub fn new(value: T) -> Self { Self { id: I::first(), value } }
+    /// Wrap a value with an id
+    pub fn with_id(id: I, value: T) -> Self { Self { id, value } }
+    pub fn value(&self) -> &T { &self.value }
+    pub fn value_mut(&mut self) -> &mut T { &mut self.value }
+    /// Unwrap the value, discarding its id
+    pub fn into_inner(self) -> T { self.value }
+}
+
+impl <I: Identifier, T> IdentifiedBy<I> for Identified<I,T> {
+    fn get_id(&self) -> I { self.id }
+    fn set_id(&mut self, id: I) { self.id = id }
+}
+
+impl <I: Identifier, T: Hash> Hash for Identified<I,T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H)
+        { self.value.hash(state) }
+}
+
+impl <I: Identifier, T> std::ops::Deref for Identified<I,T> {
+    type Target = T;
+    fn deref(&self) -> &T { &self.value }
+}
+impl <I: Identifier, T> std::ops::DerefMut for Identified<I,T> {
+    fn deref_mut(&mut self) -> &mut T { &mut self.value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdentifiedBy, Identifier};
+    use crate::{Id32, TypedId};
+    use crate::trackers::{DenseIdTracker, IdTracker};
+
+    struct Shapes;
+    type ShapeId = TypedId<Shapes, Id32>;
+
+    trait Shape: IdentifiedBy<ShapeId> {
+        fn area(&self) -> u32;
+    }
+
+    struct Square { id: ShapeId, side: u32 }
+    impl IdentifiedBy<ShapeId> for Square {
+        fn get_id(&self) -> ShapeId { self.id }
+        fn set_id(&mut self, id: ShapeId) { self.id = id }
+    }
+    impl Shape for Square {
+        fn area(&self) -> u32 { self.side * self.side }
+    }
+
+    struct Rectangle { id: ShapeId, width: u32, height: u32 }
+    impl IdentifiedBy<ShapeId> for Rectangle {
+        fn get_id(&self) -> ShapeId { self.id }
+        fn set_id(&mut self, id: ShapeId) { self.id = id }
+    }
+    impl Shape for Rectangle {
+        fn area(&self) -> u32 { self.width * self.height }
+    }
+
+    #[test]
+    fn trackers_set_ids_through_boxed_trait_objects() {
+        let mut tracker = DenseIdTracker::<ShapeId,Box<dyn Shape>>::default();
+        let square = tracker.put(Box::new(Square { id: ShapeId::first(), side: 3 }));
+        let rectangle = tracker.put(Box::new(Rectangle { id: ShapeId::first(), width: 2, height: 5 }));
+        let rectangle_id = rectangle.lock().unwrap().get_id();
+        assert_eq!(rectangle_id, ShapeId::first().next());
+        assert_eq!(tracker.get(rectangle_id).unwrap().lock().unwrap().area(), 10);
+
+        // Flattening reaches the rectangle's id through the box
+        let square_id = square.lock().unwrap().get_id();
+        tracker.remove(square_id);
+        let mapping = tracker.flatten().unwrap();
+        assert_eq!(mapping[&rectangle_id], ShapeId::first());
+        assert_eq!(rectangle.lock().unwrap().get_id(), ShapeId::first());
+        assert_eq!(tracker.get(ShapeId::first()).unwrap().lock().unwrap().area(), 10);
+    }
+}
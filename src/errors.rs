@@ -0,0 +1,8 @@
+/*!
+Messages for panics shared across the crate. Each of these indicates a bug, or an id type which cannot be tracked.
+ */
+
+pub(crate) const CONVERT_FROM_USIZE_ERROR: &str = "Ids: failed to convert from usize";
+pub(crate) const CONVERT_TO_USIZE_ERROR: &str = "Ids: failed to convert to usize";
+pub(crate) const NEW_MUTEX_POISONED_ERROR: &str = "Ids: A brand new mutex was poisoned";
+pub(crate) const RANDOM_ID_ERROR: &str = "Ids: failed to retrieve randomness from the operating system";
@@ -2,10 +2,22 @@ use std::num::TryFromIntError;
 
 use crate::base::IdImpl;
 
-const TRY_FROM_ERR: &str = "The err branch of an IdImpl's TryFrom was executed. The rust analyser claims that this result is impossible.";
+/**
+An [IdImpl] with a fixed width in bits, whose value can be widened into a [u128].
+
+This is what allows the encodings in this crate to be written once for every id width.
+ */
+pub trait SizedIdImpl: IdImpl {
+    /// The number of bits in this id
+    const BITS: u32;
+    /// Widen the value of this id into a [u128]
+    fn to_u128(self) -> u128;
+    /// Narrow a [u128] into an id of this type, returning an [Err] if it does not fit
+    fn from_u128(value: u128) -> Result<Self,TryFromIntError>;
+}
 
 /// An 8-bit [IdImpl]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Id8(pub u8);
 impl IdImpl for Id8 { 
     fn first() -> Self { Self(0) }
@@ -15,6 +27,16 @@ impl IdImpl for Id8 {
         else { Ok(Self(self.0 + 1)) }
     }
 }
+impl SizedIdImpl for Id8 {
+    const BITS: u32 = u8::BITS;
+    fn to_u128(self) -> u128 { u128::from(self.0) }
+    fn from_u128(value: u128) -> Result<Self,TryFromIntError> {
+        match u8::try_from(value) {
+            Ok(val) => Ok(Self(val)),
+            Err(err) => Err(err),
+        }
+    }
+}
 impl TryFrom<usize> for Id8 {
     type Error = TryFromIntError;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
@@ -27,15 +49,12 @@ impl TryFrom<usize> for Id8 {
 impl TryFrom<Id8> for usize {
     type Error = TryFromIntError;
     fn try_from(value: Id8) -> Result<Self, Self::Error> {
-        match usize::try_from(value.0) {
-            Ok(val) => Ok(val),
-            Err(_e) => { panic!("{}", TRY_FROM_ERR) },
-        }
+        Ok(usize::from(value.0))
     }
 }
 
 /// A 16-bit [IdImpl]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Id16(pub u16);
 impl IdImpl for Id16 { 
     fn first() -> Self { Self(0) }
@@ -45,6 +64,16 @@ impl IdImpl for Id16 {
         else { Ok(Self(self.0 + 1)) }
     }
 }
+impl SizedIdImpl for Id16 {
+    const BITS: u32 = u16::BITS;
+    fn to_u128(self) -> u128 { u128::from(self.0) }
+    fn from_u128(value: u128) -> Result<Self,TryFromIntError> {
+        match u16::try_from(value) {
+            Ok(val) => Ok(Self(val)),
+            Err(err) => Err(err),
+        }
+    }
+}
 impl TryFrom<usize> for Id16 {
     type Error = TryFromIntError;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
@@ -57,15 +86,12 @@ impl TryFrom<usize> for Id16 {
 impl TryFrom<Id16> for usize {
     type Error = TryFromIntError;
     fn try_from(value: Id16) -> Result<Self, Self::Error> {
-        match usize::try_from(value.0) {
-            Ok(val) => Ok(val),
-            Err(_e) => { panic!("{}", TRY_FROM_ERR) },
-        }
+        Ok(usize::from(value.0))
     }
 }
 
 /// A 32-bit [IdImpl]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Id32(pub u32);
 impl IdImpl for Id32 { 
     fn first() -> Self { Self(0) }
@@ -75,6 +101,16 @@ impl IdImpl for Id32 {
         else { Ok(Self(self.0 + 1)) }
     }
 }
+impl SizedIdImpl for Id32 {
+    const BITS: u32 = u32::BITS;
+    fn to_u128(self) -> u128 { u128::from(self.0) }
+    fn from_u128(value: u128) -> Result<Self,TryFromIntError> {
+        match u32::try_from(value) {
+            Ok(val) => Ok(Self(val)),
+            Err(err) => Err(err),
+        }
+    }
+}
 impl TryFrom<usize> for Id32 {
     type Error = TryFromIntError;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
@@ -87,15 +123,12 @@ impl TryFrom<usize> for Id32 {
 impl TryFrom<Id32> for usize {
     type Error = TryFromIntError;
     fn try_from(value: Id32) -> Result<Self, Self::Error> {
-        match usize::try_from(value.0) {
-            Ok(val) => Ok(val),
-            Err(_e) => { panic!("{}", TRY_FROM_ERR) },
-        }
+        usize::try_from(value.0)
     }
 }
 
 /// An 64-bit [IdImpl]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Id64(pub u64);
 impl IdImpl for Id64 { 
     fn first() -> Self { Self(0) }
@@ -105,6 +138,16 @@ impl IdImpl for Id64 {
         else { Ok(Self(self.0 + 1)) }
     }
 }
+impl SizedIdImpl for Id64 {
+    const BITS: u32 = u64::BITS;
+    fn to_u128(self) -> u128 { u128::from(self.0) }
+    fn from_u128(value: u128) -> Result<Self,TryFromIntError> {
+        match u64::try_from(value) {
+            Ok(val) => Ok(Self(val)),
+            Err(err) => Err(err),
+        }
+    }
+}
 impl TryFrom<usize> for Id64 {
     type Error = TryFromIntError;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
@@ -117,15 +160,12 @@ impl TryFrom<usize> for Id64 {
 impl TryFrom<Id64> for usize {
     type Error = TryFromIntError;
     fn try_from(value: Id64) -> Result<Self, Self::Error> {
-        match usize::try_from(value.0) {
-            Ok(val) => Ok(val),
-            Err(_e) => { panic!("{}", TRY_FROM_ERR) },
-        }
+        usize::try_from(value.0)
     }
 }
 
 /// An 128-bit [IdImpl]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Id128(pub u128);
 impl IdImpl for Id128 {
     fn first() -> Self { Self(0) }
@@ -135,6 +175,13 @@ impl IdImpl for Id128 {
         else { Ok(Self(self.0 + 1)) }
     }
 }
+impl SizedIdImpl for Id128 {
+    const BITS: u32 = u128::BITS;
+    fn to_u128(self) -> u128 { self.0 }
+    fn from_u128(value: u128) -> Result<Self,TryFromIntError> {
+        Ok(Self(value))
+    }
+}
 impl TryFrom<usize> for Id128 {
     type Error = TryFromIntError;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
@@ -147,9 +194,6 @@ impl TryFrom<usize> for Id128 {
 impl TryFrom<Id128> for usize {
     type Error = TryFromIntError;
     fn try_from(value: Id128) -> Result<Self, Self::Error> {
-        match usize::try_from(value.0) {
-            Ok(val) => Ok(val),
-            Err(_e) => { panic!("{}", TRY_FROM_ERR) },
-        }
+        usize::try_from(value.0)
     }
 }
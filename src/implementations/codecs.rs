@@ -0,0 +1,446 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use super::{SizedIdImpl, Id8, Id16, Id32, Id64, Id128};
+
+const HEX_ALPHABET: &[u8; 16] = b"0123456789abcdef";
+const BASE32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const BASE62_ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const DECIMAL_ALPHABET: &[u8; 10] = b"0123456789";
+const PROQUINT_CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+const PROQUINT_VOWELS: &[u8; 4] = b"aiou";
+const PROQUINT_SEPARATOR: char = '-';
+
+/// The quasigroup used by the Damm algorithm, which has no weak pairs of digits
+const DAMM_TABLE: [[u8; 10]; 10] = [
+    [0, 3, 1, 7, 5, 9, 8, 6, 4, 2],
+    [7, 0, 9, 2, 1, 5, 4, 8, 6, 3],
+    [4, 2, 0, 6, 8, 7, 1, 3, 5, 9],
+    [1, 7, 5, 0, 9, 8, 3, 4, 2, 6],
+    [6, 1, 2, 3, 0, 4, 5, 9, 7, 8],
+    [3, 6, 7, 4, 2, 0, 9, 5, 8, 1],
+    [5, 8, 6, 9, 7, 2, 0, 1, 3, 4],
+    [8, 9, 4, 5, 3, 6, 2, 0, 1, 7],
+    [9, 4, 3, 8, 6, 1, 7, 2, 0, 5],
+    [2, 5, 8, 1, 4, 3, 6, 7, 9, 0],
+];
+
+/// The [IdCodec] used by the [Display] and [FromStr] implementations of each id
+pub const DEFAULT_ID_CODEC: IdCodec = IdCodec::CrockfordBase32;
+
+/**
+A reason that a string or byte sequence could not be decoded into an id.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum IdDecodeError {
+    /// The input contained nothing to decode
+    Empty,
+    /// The input contained a character which is not part of the encoding, at the given character index
+    InvalidCharacter { character: char, index: usize },
+    /// The input was not the length which the encoding requires for this id width
+    WrongLength { expected: usize, found: usize },
+    /// The input was longer than any value of this id width can be written in the encoding
+    TooLong { max: usize, found: usize },
+    /// The input's most significant digit was zero, which is only written when the whole id is zero
+    LeadingZero,
+    /// The input described a value too large to fit into this id width
+    Overflow,
+    /// The input's check digit did not match the rest of it, so it was most likely mistyped
+    CheckDigitMismatch,
+}
+
+impl Display for IdDecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "cannot decode an id from empty input"),
+            Self::InvalidCharacter { character, index } => write!(f, "invalid character {:?} at index {} of id", character, index),
+            Self::WrongLength { expected, found } => write!(f, "expected an id of length {}, found length {}", expected, found),
+            Self::TooLong { max, found } => write!(f, "expected an id of length at most {}, found length {}", max, found),
+            Self::LeadingZero => write!(f, "id has a leading zero, which is not its canonical form"),
+            Self::Overflow => write!(f, "id is too large for its type"),
+            Self::CheckDigitMismatch => write!(f, "id check digit does not match, it may have been mistyped"),
+        }
+    }
+}
+
+impl std::error::Error for IdDecodeError {}
+
+/**
+A way of writing a [SizedIdImpl] as a string, and of reading it back.
+
+ - [IdCodec::Hex] writes lowercase hexadecimal, zero-padded to the full width of the id. Either case is accepted when decoding.
+ - [IdCodec::CrockfordBase32] writes Crockford's base32 without padding. Decoding is case-insensitive, and accepts `I`/`L` as `1` and `O` as `0`.
+ - [IdCodec::Base62] writes digits, then uppercase, then lowercase letters, without padding. Decoding is case-sensitive.
+
+ Since base32 and base62 are unpadded, decoding them rejects leading zeros, and input longer than the largest id of the width, so that each id has only one form.
+
+ - [IdCodec::CheckedDecimal] writes decimal followed by a Damm check digit, for ids which are typed in by hand.
+   Decoding ignores spaces and hyphens, rejects leading zeros and overlong input as base32 and base62 do,
+   and detects every single-digit error and every adjacent transposition.
+   It is intended for [crate::Id32] and [crate::Id64], whose decimal forms are short enough to read out.
+ - [IdCodec::Proquint] writes one pronounceable five-letter word for every 16 bits, such as `lusab-babad`, joined by hyphens.
+   Decoding is case-insensitive, and requires exactly as many words as the id width needs.
+   It is intended for [crate::Id16], [crate::Id32] and [crate::Id64], so that ids can be read aloud.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum IdCodec {
+    Hex,
+    CrockfordBase32,
+    Base62,
+    CheckedDecimal,
+    Proquint,
+}
+
+impl IdCodec {
+    /// Write an id as a string in this encoding
+    pub fn encode<T: SizedIdImpl>(self, id: T) -> String {
+        match self {
+            Self::Hex => {
+                let digits = T::BITS.div_ceil(4) as usize;
+                let mut encoded = encode_radix(id.to_u128(), HEX_ALPHABET);
+                while encoded.len() < digits { encoded.insert(0, '0'); }
+                encoded
+            }, Self::CrockfordBase32 => encode_radix(id.to_u128(), BASE32_ALPHABET),
+            Self::Base62 => encode_radix(id.to_u128(), BASE62_ALPHABET),
+            Self::CheckedDecimal => {
+                let mut encoded = encode_radix(id.to_u128(), DECIMAL_ALPHABET);
+                encoded.push((b'0' + damm_digit(encoded.bytes().map(|d| d - b'0'))) as char);
+                encoded
+            },
+            Self::Proquint => encode_proquints(id.to_u128(), proquint_count(T::BITS)),
+        }
+    }
+
+    /// Read an id from a string in this encoding, returning an [Err] describing the first problem found
+    pub fn decode<T: SizedIdImpl>(self, encoded: &str) -> Result<T,IdDecodeError> {
+        if encoded.is_empty() { return Err(IdDecodeError::Empty) }
+        let decoded = match self {
+            Self::Hex => {
+                let digits = T::BITS.div_ceil(4) as usize;
+                let found = encoded.chars().count();
+                if found != digits
+                    { return Err(IdDecodeError::WrongLength { expected: digits, found }) }
+                decode_radix(encoded, 16, hex_digit)
+            }, Self::CrockfordBase32 => decode_unpadded(encoded, T::BITS, BASE32_ALPHABET, base32_digit),
+            Self::Base62 => decode_unpadded(encoded, T::BITS, BASE62_ALPHABET, base62_digit),
+            Self::CheckedDecimal => decode_checked_decimal(encoded, T::BITS),
+            Self::Proquint => decode_proquints(encoded, proquint_count(T::BITS)),
+        }; let value = match decoded {
+            Ok(value) => value,
+            Err(err) => return Err(err),
+        };
+        match T::from_u128(value) {
+            Ok(id) => Ok(id),
+            Err(_) => Err(IdDecodeError::Overflow),
+        }
+    }
+}
+
+/// Write a value in the radix given by the length of an alphabet, with no padding
+fn encode_radix(mut value: u128, alphabet: &[u8]) -> String {
+    let radix = alphabet.len() as u128;
+    let mut digits = Vec::new();
+    loop {
+        digits.push(alphabet[(value % radix) as usize]);
+        value /= radix;
+        if value == 0 { break; }
+    }; digits.iter().rev().map(|d| *d as char).collect()
+}
+
+/// Read a value in the given radix, using a function which maps characters to digits
+fn decode_radix(encoded: &str, radix: u128, digit: fn(char) -> Option<u8>) -> Result<u128,IdDecodeError> {
+    let mut value: u128 = 0;
+    for (index, character) in encoded.chars().enumerate() {
+        let d = match digit(character) {
+            Some(d) => d,
+            None => return Err(IdDecodeError::InvalidCharacter { character, index }),
+        }; value = match value.checked_mul(radix).and_then(|v| v.checked_add(u128::from(d))) {
+            Some(v) => v,
+            None => return Err(IdDecodeError::Overflow),
+        };
+    }; return Ok(value);
+}
+
+/// Read a value written by [encode_radix] for an id of the given width, rejecting any other way of writing it
+fn decode_unpadded(encoded: &str, bits: u32, alphabet: &[u8], digit: fn(char) -> Option<u8>) -> Result<u128,IdDecodeError> {
+    let largest = if bits >= u128::BITS { u128::MAX } else { (1 << bits) - 1 };
+    let max = encode_radix(largest, alphabet).len();
+    let found = encoded.chars().count();
+    if found > max
+        { return Err(IdDecodeError::TooLong { max, found }) }
+    if found > 1 && encoded.chars().next().and_then(digit) == Some(0)
+        { return Err(IdDecodeError::LeadingZero) }
+    decode_radix(encoded, alphabet.len() as u128, digit)
+}
+
+/**
+Read a decimal value followed by a Damm check digit, skipping any separators.
+
+Like [decode_unpadded], this rejects leading zeros, and more digits than the largest id of the width is written with.
+A mistyped zero at the start is reported as a [IdDecodeError::CheckDigitMismatch], since that is the likelier cause.
+Lengths are counted in digits, not including separators.
+ */
+fn decode_checked_decimal(encoded: &str, bits: u32) -> Result<u128,IdDecodeError> {
+    let mut digits = Vec::new();
+    for (index, character) in encoded.chars().enumerate() {
+        match character {
+            ' ' | '-' => {},
+            '0'..='9' => digits.push(character as u8 - b'0'),
+            _ => return Err(IdDecodeError::InvalidCharacter { character, index }),
+        }
+    }; if digits.len() < 2
+        { return Err(IdDecodeError::WrongLength { expected: 2, found: digits.len() }) }
+    let largest = if bits >= u128::BITS { u128::MAX } else { (1 << bits) - 1 };
+    let max = encode_radix(largest, DECIMAL_ALPHABET).len() + 1;
+    if digits.len() > max
+        { return Err(IdDecodeError::TooLong { max, found: digits.len() }) }
+    if damm_digit(digits.iter().copied()) != 0
+        { return Err(IdDecodeError::CheckDigitMismatch) }
+    if digits.len() > 2 && digits[0] == 0
+        { return Err(IdDecodeError::LeadingZero) }
+    // Drop the check digit, and read what remains
+    let mut value: u128 = 0;
+    for d in &digits[..digits.len()-1] {
+        value = match value.checked_mul(10).and_then(|v| v.checked_add(u128::from(*d))) {
+            Some(v) => v,
+            None => return Err(IdDecodeError::Overflow),
+        };
+    }; return Ok(value);
+}
+
+/// Run the Damm algorithm over some decimal digits. Appending the result as a digit gives a sequence which checks to zero.
+fn damm_digit<Itr: Iterator<Item = u8>>(digits: Itr) -> u8 {
+    digits.fold(0, |interim, d| DAMM_TABLE[interim as usize][d as usize])
+}
+
+/// Get the number of proquint words needed to write an id of the given width
+fn proquint_count(bits: u32) -> usize {
+    bits.div_ceil(16) as usize
+}
+
+/// Write the lowest `count` 16-bit words of a value as proquints, most significant first
+fn encode_proquints(value: u128, count: usize) -> String {
+    let mut words = Vec::new();
+    for n in (0..count).rev() {
+        let word = (value >> (16 * n)) as u16;
+        words.push(String::from_iter([
+            PROQUINT_CONSONANTS[(word >> 12) as usize & 0xF] as char,
+            PROQUINT_VOWELS[(word >> 10) as usize & 0x3] as char,
+            PROQUINT_CONSONANTS[(word >> 6) as usize & 0xF] as char,
+            PROQUINT_VOWELS[(word >> 4) as usize & 0x3] as char,
+            PROQUINT_CONSONANTS[word as usize & 0xF] as char,
+        ]));
+    }; words.join(&PROQUINT_SEPARATOR.to_string())
+}
+
+/// Read exactly `count` hyphen-separated proquints into a value
+fn decode_proquints(encoded: &str, count: usize) -> Result<u128,IdDecodeError> {
+    let expected = count * 6 - 1;
+    let found = encoded.chars().count();
+    if found != expected
+        { return Err(IdDecodeError::WrongLength { expected, found }) }
+    let mut value: u128 = 0;
+    for (index, character) in encoded.chars().enumerate() {
+        if !character.is_ascii()
+            { return Err(IdDecodeError::InvalidCharacter { character, index }) }
+        let lower = character.to_ascii_lowercase() as u8;
+        let position = index % 6;
+        let (bits, digit) = match position {
+            5 if character == PROQUINT_SEPARATOR => continue,
+            0 | 2 | 4 => (4, PROQUINT_CONSONANTS.iter().position(|c| *c == lower)),
+            1 | 3 => (2, PROQUINT_VOWELS.iter().position(|v| *v == lower)),
+            _ => (0, None),
+        }; match digit {
+            Some(d) => value = (value << bits) | d as u128,
+            None => return Err(IdDecodeError::InvalidCharacter { character, index }),
+        }
+    }; return Ok(value);
+}
+
+fn hex_digit(c: char) -> Option<u8> {
+    c.to_digit(16).map(|d| d as u8)
+}
+
+fn base32_digit(c: char) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        'O' => Some(0),
+        'I' | 'L' => Some(1),
+        upper => BASE32_ALPHABET.iter().position(|a| *a as char == upper).map(|d| d as u8),
+    }
+}
+
+fn base62_digit(c: char) -> Option<u8> {
+    match c {
+        '0'..='9' => Some(c as u8 - b'0'),
+        'A'..='Z' => Some(c as u8 - b'A' + 10),
+        'a'..='z' => Some(c as u8 - b'a' + 36),
+        _ => None,
+    }
+}
+
+/// Implement [Display] and [FromStr] for id types using the [DEFAULT_ID_CODEC]
+macro_rules! impl_default_codec {
+    ($($id:ty),*) => { $(
+        impl Display for $id {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}", DEFAULT_ID_CODEC.encode(*self)) }
+        }
+        impl FromStr for $id {
+            type Err = IdDecodeError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> { DEFAULT_ID_CODEC.decode(s) }
+        }
+    )* };
+}
+
+impl_default_codec!(Id8, Id16, Id32, Id64, Id128);
+
+#[cfg(test)]
+mod tests {
+    use super::{IdCodec, IdDecodeError};
+    use crate::{Id8, Id16, Id32, Id64, Id128};
+
+    const RADIX_CODECS: [IdCodec; 3] = [IdCodec::Hex, IdCodec::CrockfordBase32, IdCodec::Base62];
+
+    #[test]
+    fn radix_codecs_round_trip_every_width() {
+        for codec in RADIX_CODECS {
+            for n in [0, 1, 31, 32, 61, 62, u8::MAX] {
+                assert_eq!(codec.decode::<Id8>(&codec.encode(Id8(n))), Ok(Id8(n)));
+            }
+            assert_eq!(codec.decode::<Id16>(&codec.encode(Id16(u16::MAX))), Ok(Id16(u16::MAX)));
+            assert_eq!(codec.decode::<Id32>(&codec.encode(Id32(u32::MAX))), Ok(Id32(u32::MAX)));
+            assert_eq!(codec.decode::<Id64>(&codec.encode(Id64(u64::MAX))), Ok(Id64(u64::MAX)));
+            assert_eq!(codec.decode::<Id128>(&codec.encode(Id128(u128::MAX))), Ok(Id128(u128::MAX)));
+            assert_eq!(codec.decode::<Id128>(&codec.encode(Id128(0))), Ok(Id128(0)));
+        }
+    }
+
+    #[test]
+    fn radix_codecs_encode_known_values() {
+        assert_eq!(IdCodec::Hex.encode(Id16(0xAB)), "00ab");
+        assert_eq!(IdCodec::CrockfordBase32.encode(Id32(0)), "0");
+        assert_eq!(IdCodec::CrockfordBase32.encode(Id32(32 * 10 + 31)), "AZ");
+        assert_eq!(IdCodec::Base62.encode(Id32(61)), "z");
+        assert_eq!(IdCodec::Base62.encode(Id32(62)), "10");
+    }
+
+    #[test]
+    fn display_and_from_str_use_the_default_codec() {
+        assert_eq!(Id64(1234567).to_string(), IdCodec::CrockfordBase32.encode(Id64(1234567)));
+        assert_eq!("15NM7".parse::<Id64>(), IdCodec::CrockfordBase32.decode::<Id64>("15NM7"));
+    }
+
+    #[test]
+    fn crockford_base32_accepts_aliases_and_either_case() {
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id32>("az"), Ok(Id32(32 * 10 + 31)));
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id32>("1O"), IdCodec::CrockfordBase32.decode::<Id32>("10"));
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id32>("I"), Ok(Id32(1)));
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id32>("l"), Ok(Id32(1)));
+    }
+
+    #[test]
+    fn unpadded_codecs_reject_non_canonical_input() {
+        for codec in [IdCodec::CrockfordBase32, IdCodec::Base62] {
+            assert_eq!(codec.decode::<Id8>("01"), Err(IdDecodeError::LeadingZero));
+            assert_eq!(codec.decode::<Id8>("00"), Err(IdDecodeError::LeadingZero));
+            assert_eq!(codec.decode::<Id8>("00000000000000000001"), Err(IdDecodeError::TooLong { max: 2, found: 20 }));
+            assert_eq!(codec.decode::<Id8>("0"), Ok(Id8(0)));
+        }
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id8>("O1"), Err(IdDecodeError::LeadingZero));
+    }
+
+    #[test]
+    fn radix_codecs_reject_malformed_input() {
+        for codec in RADIX_CODECS {
+            assert_eq!(codec.decode::<Id32>(""), Err(IdDecodeError::Empty));
+        }
+        assert_eq!(IdCodec::Hex.decode::<Id16>("0ab"), Err(IdDecodeError::WrongLength { expected: 4, found: 3 }));
+        assert_eq!(IdCodec::Hex.decode::<Id16>("00ag"), Err(IdDecodeError::InvalidCharacter { character: 'g', index: 3 }));
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id32>("AU"), Err(IdDecodeError::InvalidCharacter { character: 'U', index: 1 }));
+        assert_eq!(IdCodec::Base62.decode::<Id32>("a-b"), Err(IdDecodeError::InvalidCharacter { character: '-', index: 1 }));
+        // Short enough to be an Id8, but larger than its largest value
+        assert_eq!(IdCodec::CrockfordBase32.decode::<Id8>("80"), Err(IdDecodeError::Overflow));
+        assert_eq!(IdCodec::Base62.decode::<Id8>("5A"), Err(IdDecodeError::Overflow));
+    }
+
+    #[test]
+    fn checked_decimal_round_trips_and_ignores_separators() {
+        for n in [0, 7, 1234, u32::MAX] {
+            assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>(&IdCodec::CheckedDecimal.encode(Id32(n))), Ok(Id32(n)));
+        }
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id64>(&IdCodec::CheckedDecimal.encode(Id64(u64::MAX))), Ok(Id64(u64::MAX)));
+        // 572 has the check digit 4, from the usual worked example of the Damm algorithm
+        assert_eq!(IdCodec::CheckedDecimal.encode(Id32(572)), "5724");
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("57-2 4"), Ok(Id32(572)));
+    }
+
+    #[test]
+    fn checked_decimal_detects_single_digit_errors() {
+        let encoded = IdCodec::CheckedDecimal.encode(Id64(9_081_726_354));
+        for index in 0..encoded.len() {
+            for replacement in '0'..='9' {
+                let mut mistyped: Vec<char> = encoded.chars().collect();
+                if mistyped[index] == replacement { continue }
+                mistyped[index] = replacement;
+                let mistyped: String = mistyped.into_iter().collect();
+                assert_eq!(IdCodec::CheckedDecimal.decode::<Id64>(&mistyped), Err(IdDecodeError::CheckDigitMismatch), "{}", mistyped);
+            }
+        }
+    }
+
+    #[test]
+    fn checked_decimal_detects_adjacent_transpositions() {
+        let encoded = IdCodec::CheckedDecimal.encode(Id64(9_081_726_354));
+        for index in 0..encoded.len()-1 {
+            let mut swapped: Vec<char> = encoded.chars().collect();
+            if swapped[index] == swapped[index+1] { continue }
+            swapped.swap(index, index+1);
+            let swapped: String = swapped.into_iter().collect();
+            assert_eq!(IdCodec::CheckedDecimal.decode::<Id64>(&swapped), Err(IdDecodeError::CheckDigitMismatch), "{}", swapped);
+        }
+    }
+
+    #[test]
+    fn checked_decimal_rejects_malformed_input() {
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("5"), Err(IdDecodeError::WrongLength { expected: 2, found: 1 }));
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("57x4"), Err(IdDecodeError::InvalidCharacter { character: 'x', index: 2 }));
+        let too_large = IdCodec::CheckedDecimal.encode(Id64(u64::from(u32::MAX) + 1));
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>(&too_large), Err(IdDecodeError::Overflow));
+    }
+
+    #[test]
+    fn checked_decimal_rejects_non_canonical_input() {
+        // 0572 checks to zero just as 5724 does, since leading zeros do not change the Damm digit
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("05724"), Err(IdDecodeError::LeadingZero));
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("000"), Err(IdDecodeError::LeadingZero));
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("0-0"), Ok(Id32(0)));
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("00000000000000005724"), Err(IdDecodeError::TooLong { max: 11, found: 20 }));
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id32>("0"), Err(IdDecodeError::WrongLength { expected: 2, found: 1 }));
+        assert_eq!(IdCodec::CheckedDecimal.encode(Id32(0)), "00");
+        // Separators do not count towards the length
+        assert_eq!(IdCodec::CheckedDecimal.decode::<Id8>("2-5-5-2"), IdCodec::CheckedDecimal.decode::<Id8>("2552"));
+    }
+
+    #[test]
+    fn proquints_round_trip_every_width() {
+        assert_eq!(IdCodec::Proquint.encode(Id16(0)), "babab");
+        assert_eq!(IdCodec::Proquint.encode(Id16(u16::MAX)), "zuzuz");
+        // 127.0.0.1, from the proquint specification
+        assert_eq!(IdCodec::Proquint.encode(Id32(0x7F00_0001)), "lusab-babad");
+        assert_eq!(IdCodec::Proquint.decode::<Id32>("LUSAB-babad"), Ok(Id32(0x7F00_0001)));
+        assert_eq!(IdCodec::Proquint.decode::<Id8>(&IdCodec::Proquint.encode(Id8(200))), Ok(Id8(200)));
+        assert_eq!(IdCodec::Proquint.decode::<Id64>(&IdCodec::Proquint.encode(Id64(u64::MAX - 5))), Ok(Id64(u64::MAX - 5)));
+        assert_eq!(IdCodec::Proquint.decode::<Id128>(&IdCodec::Proquint.encode(Id128(u128::MAX))), Ok(Id128(u128::MAX)));
+    }
+
+    #[test]
+    fn proquints_reject_malformed_input() {
+        assert_eq!(IdCodec::Proquint.decode::<Id32>("lusab"), Err(IdDecodeError::WrongLength { expected: 11, found: 5 }));
+        assert_eq!(IdCodec::Proquint.decode::<Id32>("lusab_babad"), Err(IdDecodeError::InvalidCharacter { character: '_', index: 5 }));
+        assert_eq!(IdCodec::Proquint.decode::<Id16>("lasab"), Ok(Id16(0x7300)));
+        assert_eq!(IdCodec::Proquint.decode::<Id16>("lesab"), Err(IdDecodeError::InvalidCharacter { character: 'e', index: 1 }));
+        // Non-ASCII characters must not be truncated onto ASCII ones, such as U+0162 onto 'b'
+        assert_eq!(IdCodec::Proquint.decode::<Id16>("\u{162}abab"), Err(IdDecodeError::InvalidCharacter { character: '\u{162}', index: 0 }));
+        // An Id8 needs a whole word, but its value must still fit
+        assert_eq!(IdCodec::Proquint.decode::<Id8>("dabab"), Err(IdDecodeError::Overflow));
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::{Identifier, KeyBytes};
+
+/// The character placed between the parent and child of a [CompositeId] when it is written as a string
+pub const PATH_SEPARATOR: char = '/';
+
+/**
+Either a single [Identifier], or a [CompositeId] path made of them. This is what a [CompositeId] may use as its parent.
+ */
+pub trait IdentifierPath: Clone + Copy + PartialEq + Eq + Hash + Debug {}
+impl <I: Identifier> IdentifierPath for I {}
+impl <P: IdentifierPath, C: Identifier> IdentifierPath for CompositeId<P,C> {}
+
+/**
+An id made up of a parent id and a child id, such as a paragraph within a document.
+
+Child ids are only unique within their parent, so `(document 1, paragraph 0)` and `(document 2, paragraph 0)` are different ids.
+Longer paths can be made by nesting, as in `CompositeId<CompositeId<A,B>,C>`.
+Composite ids are ordered, and written as key bytes, by parent first, so all children of a parent sort together.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct CompositeId<P: IdentifierPath, C: Identifier> {
+    parent: P,
+    child: C,
+}
+
+impl <P: IdentifierPath, C: Identifier> CompositeId<P,C> {
+    pub fn new(parent: P, child: C) -> Self { Self { parent, child } }
+    /// Get the id of the parent which this id is scoped within
+    pub fn parent(&self) -> P { self.parent }
+    /// Get the id of the child, which is only unique within its parent
+    pub fn child(&self) -> C { self.child }
+
+    /// Get the first child id within a parent
+    pub fn first(parent: P) -> Self { Self::new(parent, C::first()) }
+    /// Get the next child id within the same parent, after this one
+    pub fn next(self) -> Self { Self::new(self.parent, self.child.next()) }
+}
+
+impl <P: IdentifierPath, C: Identifier> From<(P,C)> for CompositeId<P,C> {
+    fn from((parent, child): (P,C)) -> Self { Self::new(parent, child) }
+}
+impl <P: IdentifierPath, C: Identifier> From<CompositeId<P,C>> for (P,C) {
+    fn from(id: CompositeId<P,C>) -> Self { (id.parent, id.child) }
+}
+
+impl <P: IdentifierPath + Display, C: Identifier + Display> Display for CompositeId<P,C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.parent, PATH_SEPARATOR, self.child)
+    }
+}
+
+/**
+A reason that a string could not be parsed into a [CompositeId].
+ */
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum CompositeParseError<PE, CE> {
+    /// The string did not contain a [PATH_SEPARATOR]
+    MissingSeparator,
+    /// The part before the last separator could not be parsed into the parent id
+    Parent(PE),
+    /// The part after the last separator could not be parsed into the child id
+    Child(CE),
+}
+
+impl <PE: Display, CE: Display> Display for CompositeParseError<PE,CE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSeparator => write!(f, "expected a composite id containing {:?}", PATH_SEPARATOR),
+            Self::Parent(err) => write!(f, "invalid parent id: {}", err),
+            Self::Child(err) => write!(f, "invalid child id: {}", err),
+        }
+    }
+}
+
+impl <PE: Debug + Display, CE: Debug + Display> std::error::Error for CompositeParseError<PE,CE> {}
+
+impl <P: IdentifierPath + FromStr, C: Identifier + FromStr> FromStr for CompositeId<P,C> {
+    type Err = CompositeParseError<P::Err, C::Err>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (parent, child) = match s.rsplit_once(PATH_SEPARATOR) {
+            Some(parts) => parts,
+            None => return Err(CompositeParseError::MissingSeparator),
+        }; match (parent.parse(), child.parse()) {
+            (Ok(parent), Ok(child)) => Ok(Self::new(parent, child)),
+            (Err(err), _) => Err(CompositeParseError::Parent(err)),
+            (_, Err(err)) => Err(CompositeParseError::Child(err)),
+        }
+    }
+}
+
+impl <P: IdentifierPath + KeyBytes, C: Identifier + KeyBytes> KeyBytes for CompositeId<P,C> {
+    const KEY_LEN: usize = P::KEY_LEN + C::KEY_LEN;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { (self.parent, self.child).write_key_bytes(out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { <(P,C)>::read_key_bytes(bytes).into() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompositeId, CompositeParseError};
+    use crate::{Id8, Id32, Id64, IdDecodeError, KeyBytes, TypedId};
+
+    struct Books;
+    struct Chapters;
+    struct Lines;
+    type BookId = TypedId<Books, Id64>;
+    type ChapterId = TypedId<Chapters, Id32>;
+    type LineId = TypedId<Lines, Id8>;
+
+    #[test]
+    fn nested_ids_round_trip_through_strings_and_key_bytes() {
+        let id = CompositeId::new(CompositeId::new(BookId::new(Id64(9000)), ChapterId::new(Id32(12))), LineId::new(Id8(3)));
+        assert_eq!(id.to_string(), format!("{}/{}/{}", Id64(9000), Id32(12), Id8(3)));
+        assert_eq!(id.to_string().parse(), Ok(id));
+        assert_eq!(CompositeId::from_key_bytes(&id.to_key_bytes()), Ok(id));
+        assert_eq!("12".parse::<CompositeId<ChapterId,LineId>>(), Err(CompositeParseError::MissingSeparator));
+        assert_eq!("12/".parse::<CompositeId<ChapterId,LineId>>(), Err(CompositeParseError::Child(IdDecodeError::Empty)));
+    }
+
+    #[test]
+    fn children_of_a_parent_sort_together() {
+        let id = |chapter, line| CompositeId::new(ChapterId::new(Id32(chapter)), ChapterId::new(Id32(line)));
+        let mut ids = vec![id(2, 0), id(1, 300), id(1, 4)];
+        ids.sort_by_key(|id| id.to_key_bytes());
+        assert_eq!(ids, vec![id(1, 4), id(1, 300), id(2, 0)]);
+    }
+}
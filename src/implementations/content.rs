@@ -0,0 +1,61 @@
+use std::hash::{Hash, Hasher};
+
+use super::Id128;
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013B;
+
+/**
+A [Hasher] which produces 128-bit hashes, for deriving ids from the content of values.
+
+This is the 128-bit FNV-1a hash. It is fast and well distributed, but is not cryptographic,
+so it should not be used where an adversary could benefit from creating collisions.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ContentHasher {
+    state: u128,
+}
+
+impl ContentHasher {
+    /// Get the full 128-bit hash of everything written so far
+    pub fn finish128(&self) -> u128 { self.state }
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self { state: FNV_OFFSET_BASIS }
+    }
+}
+
+impl Hasher for ContentHasher {
+    fn finish(&self) -> u64 { (self.state ^ (self.state >> 64)) as u64 }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= u128::from(*byte);
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/**
+Get an id derived from the content of some bytes, such as a serialized value.
+
+Identical bytes always give the same id, on every platform.
+ */
+pub fn content_id_of_bytes(bytes: &[u8]) -> Id128 {
+    let mut hasher = ContentHasher::default();
+    hasher.write(bytes);
+    Id128(hasher.finish128())
+}
+
+/**
+Get an id derived from the content of a value, through its [Hash] implementation.
+
+Equal values always give the same id within a program, but since [Hash] implementations may depend on the platform,
+[content_id_of_bytes] should be used on a serialized form if ids must be stable between machines.
+ */
+pub fn content_id<V: Hash + ?Sized>(value: &V) -> Id128 {
+    let mut hasher = ContentHasher::default();
+    value.hash(&mut hasher);
+    Id128(hasher.finish128())
+}
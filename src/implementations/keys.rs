@@ -0,0 +1,169 @@
+use crate::{IdDecodeError, IdPrefix, Prefixed};
+
+use super::{SizedIdImpl, Id8, Id16, Id32, Id64, Id128};
+
+/**
+A value which can be written as a fixed number of bytes, whose lexicographic order matches the order of the values.
+
+This makes ids usable as keys in ordered key-value stores.
+Ids are written big-endian, and tuples are written as the concatenation of their elements,
+so tuples of ids sort by their first element, then their second, and so on.
+ */
+pub trait KeyBytes: Sized {
+    /// The number of bytes which this value is always written as
+    const KEY_LEN: usize;
+
+    /// Append the key bytes of this value to a buffer
+    fn write_key_bytes(&self, out: &mut Vec<u8>);
+    /**
+    Read a value from exactly [KeyBytes::KEY_LEN] bytes, panicking if there are fewer.
+
+    This is only for implementing the trait, as the length is checked once by [KeyBytes::from_key_bytes] rather than by each element of a tuple.
+    Use [KeyBytes::from_key_bytes] to read untrusted input.
+     */
+    #[doc(hidden)]
+    fn read_key_bytes(bytes: &[u8]) -> Self;
+
+    /// Get the key bytes of this value
+    fn to_key_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::KEY_LEN);
+        self.write_key_bytes(&mut out);
+        out
+    }
+    /// Read a value from its key bytes, returning an [Err] if there are the wrong number of them
+    fn from_key_bytes(bytes: &[u8]) -> Result<Self,IdDecodeError> {
+        if bytes.len() != Self::KEY_LEN
+            { return Err(IdDecodeError::WrongLength { expected: Self::KEY_LEN, found: bytes.len() }) }
+        Ok(Self::read_key_bytes(bytes))
+    }
+}
+
+/// Append the lowest `T::BITS` bits of an id to a buffer, most significant byte first
+fn write_sized<T: SizedIdImpl>(id: T, out: &mut Vec<u8>) {
+    let len = (T::BITS / 8) as usize;
+    out.extend_from_slice(&id.to_u128().to_be_bytes()[16 - len..]);
+}
+
+/// Read an id from exactly `T::BITS / 8` big-endian bytes
+fn read_sized<T: SizedIdImpl>(bytes: &[u8]) -> T {
+    let value = bytes.iter().fold(0u128, |value, b| (value << 8) | u128::from(*b));
+    T::from_u128(value).expect("Ids: key bytes of the correct length did not fit into their id")
+}
+
+impl KeyBytes for Id8 {
+    const KEY_LEN: usize = 1;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { write_sized(*self, out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { read_sized(bytes) }
+}
+
+impl KeyBytes for Id16 {
+    const KEY_LEN: usize = 2;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { write_sized(*self, out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { read_sized(bytes) }
+}
+
+impl KeyBytes for Id32 {
+    const KEY_LEN: usize = 4;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { write_sized(*self, out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { read_sized(bytes) }
+}
+
+impl KeyBytes for Id64 {
+    const KEY_LEN: usize = 8;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { write_sized(*self, out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { read_sized(bytes) }
+}
+
+impl KeyBytes for Id128 {
+    const KEY_LEN: usize = 16;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { write_sized(*self, out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { read_sized(bytes) }
+}
+
+impl <P: IdPrefix, I: KeyBytes> KeyBytes for Prefixed<P,I> {
+    const KEY_LEN: usize = I::KEY_LEN;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { self.id().write_key_bytes(out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { Self::new(I::read_key_bytes(bytes)) }
+}
+
+impl <A: KeyBytes, B: KeyBytes> KeyBytes for (A,B) {
+    const KEY_LEN: usize = A::KEY_LEN + B::KEY_LEN;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) {
+        self.0.write_key_bytes(out);
+        self.1.write_key_bytes(out);
+    }
+    fn read_key_bytes(bytes: &[u8]) -> Self {
+        let (a, b) = bytes.split_at(A::KEY_LEN);
+        (A::read_key_bytes(a), B::read_key_bytes(b))
+    }
+}
+
+impl <A: KeyBytes, B: KeyBytes, C: KeyBytes> KeyBytes for (A,B,C) {
+    const KEY_LEN: usize = A::KEY_LEN + B::KEY_LEN + C::KEY_LEN;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) {
+        self.0.write_key_bytes(out);
+        self.1.write_key_bytes(out);
+        self.2.write_key_bytes(out);
+    }
+    fn read_key_bytes(bytes: &[u8]) -> Self {
+        let (a, rest) = bytes.split_at(A::KEY_LEN);
+        let (b, c) = rest.split_at(B::KEY_LEN);
+        (A::read_key_bytes(a), B::read_key_bytes(b), C::read_key_bytes(c))
+    }
+}
+
+impl <A: KeyBytes, B: KeyBytes, C: KeyBytes, D: KeyBytes> KeyBytes for (A,B,C,D) {
+    const KEY_LEN: usize = A::KEY_LEN + B::KEY_LEN + C::KEY_LEN + D::KEY_LEN;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) {
+        self.0.write_key_bytes(out);
+        self.1.write_key_bytes(out);
+        self.2.write_key_bytes(out);
+        self.3.write_key_bytes(out);
+    }
+    fn read_key_bytes(bytes: &[u8]) -> Self {
+        let (a, rest) = bytes.split_at(A::KEY_LEN);
+        let (b, rest) = rest.split_at(B::KEY_LEN);
+        let (c, d) = rest.split_at(C::KEY_LEN);
+        (A::read_key_bytes(a), B::read_key_bytes(b), C::read_key_bytes(c), D::read_key_bytes(d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyBytes;
+    use crate::{IdDecodeError, Id8, Id16, Id32, Id64, Id128};
+
+    /// Check that sorting some values by their key bytes sorts them numerically
+    fn assert_order_preserved<T: KeyBytes + Ord + Clone + std::fmt::Debug>(mut values: Vec<T>) {
+        let mut by_bytes = values.clone();
+        by_bytes.sort_by_key(|value| value.to_key_bytes());
+        values.sort();
+        assert_eq!(by_bytes, values);
+    }
+
+    #[test]
+    fn byte_order_matches_numeric_order() {
+        // Values which sort differently as little-endian bytes, or as unpadded digits
+        assert_order_preserved(vec![Id8(0), Id8(255), Id8(1), Id8(128), Id8(127)]);
+        assert_order_preserved(vec![Id16(0x0100), Id16(0x00FF), Id16(0xFF00), Id16(0x0001), Id16(0)]);
+        assert_order_preserved(vec![Id32(u32::MAX), Id32(256), Id32(255), Id32(65536), Id32(1)]);
+        assert_order_preserved(vec![Id64(u64::MAX), Id64(1 << 32), Id64(u64::from(u32::MAX)), Id64(0)]);
+        assert_order_preserved(vec![Id128(u128::MAX), Id128(1 << 64), Id128(u128::from(u64::MAX)), Id128(2)]);
+    }
+
+    #[test]
+    fn tuples_sort_by_each_element_in_turn() {
+        assert_order_preserved(vec![(Id16(1), Id8(0)), (Id16(0), Id8(255)), (Id16(256), Id8(1)), (Id16(1), Id8(2))]);
+        assert_order_preserved(vec![(Id8(1), Id32(0), Id8(0)), (Id8(0), Id32(u32::MAX), Id8(9)), (Id8(1), Id32(0), Id8(3))]);
+    }
+
+    #[test]
+    fn key_bytes_round_trip_and_check_their_length() {
+        let key = (Id32(7), Id64(u64::MAX - 1), Id8(3), Id16(0x1234));
+        let bytes = key.to_key_bytes();
+        assert_eq!(bytes.len(), 15);
+        assert_eq!(<(Id32,Id64,Id8,Id16)>::from_key_bytes(&bytes), Ok(key));
+        assert_eq!(<(Id32,Id64)>::from_key_bytes(&bytes[..11]), Err(IdDecodeError::WrongLength { expected: 12, found: 11 }));
+        assert_eq!(Id32::from_key_bytes(&[]), Err(IdDecodeError::WrongLength { expected: 4, found: 0 }));
+    }
+}
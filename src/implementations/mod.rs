@@ -1,3 +1,22 @@
 mod by_size;
+mod random;
+mod obfuscation;
+mod codecs;
+mod prefixed;
+mod keys;
+mod varints;
+mod typed;
+mod composite;
+mod packed;
+mod content;
 
 pub use by_size::*;
+pub use obfuscation::*;
+pub use codecs::*;
+pub use prefixed::*;
+pub use keys::*;
+pub use varints::*;
+pub use typed::*;
+pub use composite::*;
+pub use packed::*;
+pub use content::*;
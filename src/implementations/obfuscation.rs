@@ -0,0 +1,118 @@
+use super::SizedIdImpl;
+
+const ROUNDS: usize = 8;
+const CONVERT_FROM_U128_ERROR: &str = "Ids: a permuted id did not fit back into its own width";
+
+/**
+A keyed, reversible permutation over the values of every [SizedIdImpl] width.
+
+This allows ids to be stored densely, while exposing values which look random and so do not reveal how many ids have been issued.
+The permutation is a balanced Feistel network, so [IdObfuscator::reveal] always undoes [IdObfuscator::obfuscate] for the same key.
+
+Note that this is obfuscation, not encryption. It should not be relied upon to keep ids secret from a determined attacker.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct IdObfuscator {
+    round_keys: [u64; ROUNDS],
+}
+
+impl IdObfuscator {
+    /// Create an obfuscator from a secret key. The same key must be used to reveal ids later.
+    pub fn new(key: u128) -> Self {
+        let mut state = (key as u64) ^ ((key >> 64) as u64).rotate_left(32);
+        let mut round_keys = [0; ROUNDS];
+        for round_key in round_keys.iter_mut() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            *round_key = mix(state);
+        }
+        Self { round_keys }
+    }
+
+    /// Map an id onto the obfuscated id which should be exposed externally
+    pub fn obfuscate<T: SizedIdImpl>(&self, id: T) -> T {
+        let half_bits = T::BITS / 2;
+        let mask = half_mask(half_bits);
+        let value = id.to_u128();
+        let (mut left, mut right) = ((value >> half_bits) as u64, value as u64 & mask);
+        for round_key in self.round_keys.iter() {
+            let next_right = left ^ (mix(right ^ round_key) & mask);
+            left = right;
+            right = next_right;
+        }
+        T::from_u128((u128::from(left) << half_bits) | u128::from(right)).expect(CONVERT_FROM_U128_ERROR)
+    }
+
+    /// Map an obfuscated id back onto the id it was created from
+    pub fn reveal<T: SizedIdImpl>(&self, id: T) -> T {
+        let half_bits = T::BITS / 2;
+        let mask = half_mask(half_bits);
+        let value = id.to_u128();
+        let (mut left, mut right) = ((value >> half_bits) as u64, value as u64 & mask);
+        for round_key in self.round_keys.iter().rev() {
+            let previous_left = right ^ (mix(left ^ round_key) & mask);
+            right = left;
+            left = previous_left;
+        }
+        T::from_u128((u128::from(left) << half_bits) | u128::from(right)).expect(CONVERT_FROM_U128_ERROR)
+    }
+}
+
+/// Get a mask covering the lowest `bits` bits of a u64
+fn half_mask(bits: u32) -> u64 {
+    if bits >= u64::BITS { u64::MAX }
+    else { (1 << bits) - 1 }
+}
+
+/// The Feistel round function: a bijective 64-bit mixer (the splitmix64 finaliser)
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::IdObfuscator;
+    use crate::{Id8, Id16, Id32, Id64, Id128};
+
+    const KEY: u128 = 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210;
+
+    #[test]
+    fn permutes_every_id8_and_id16() {
+        let obfuscator = IdObfuscator::new(KEY);
+        let obfuscated: HashSet<Id8> = (0..=u8::MAX).map(|n| obfuscator.obfuscate(Id8(n))).collect();
+        assert_eq!(obfuscated.len(), 256);
+        let obfuscated: HashSet<Id16> = (0..=u16::MAX).map(|n| obfuscator.obfuscate(Id16(n))).collect();
+        assert_eq!(obfuscated.len(), 65536);
+        for n in 0..=u16::MAX
+            { assert_eq!(obfuscator.reveal(obfuscator.obfuscate(Id16(n))), Id16(n)); }
+    }
+
+    #[test]
+    fn reveal_undoes_obfuscate_for_wide_ids() {
+        let obfuscator = IdObfuscator::new(KEY);
+        for n in [0, 1, 2, 1000, u32::MAX - 1, u32::MAX] {
+            assert_eq!(obfuscator.reveal(obfuscator.obfuscate(Id32(n))), Id32(n));
+            assert_eq!(obfuscator.reveal(obfuscator.obfuscate(Id64(u64::from(n) << 20))), Id64(u64::from(n) << 20));
+            assert_eq!(obfuscator.reveal(obfuscator.obfuscate(Id128(u128::from(n) << 90))), Id128(u128::from(n) << 90));
+        }
+        assert_eq!(obfuscator.reveal(obfuscator.obfuscate(Id64(u64::MAX))), Id64(u64::MAX));
+        assert_eq!(obfuscator.reveal(obfuscator.obfuscate(Id128(u128::MAX))), Id128(u128::MAX));
+    }
+
+    #[test]
+    fn sequential_ids_are_scattered() {
+        let obfuscator = IdObfuscator::new(KEY);
+        let obfuscated: Vec<Id32> = (0..4).map(|n| obfuscator.obfuscate(Id32(n))).collect();
+        assert!(obfuscated.windows(2).all(|pair| pair[0].0.abs_diff(pair[1].0) > 1));
+    }
+
+    #[test]
+    fn different_keys_give_different_permutations() {
+        let (a, b) = (IdObfuscator::new(KEY), IdObfuscator::new(KEY + 1));
+        assert_ne!(a.obfuscate(Id64(42)), b.obfuscate(Id64(42)));
+        assert_ne!(b.reveal(a.obfuscate(Id64(42))), Id64(42));
+    }
+}
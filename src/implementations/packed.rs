@@ -0,0 +1,146 @@
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+
+/**
+An id which packs a small kind discriminant and an index into a single [u32], for references which may point at one of several kinds of entity.
+
+The highest `KIND_BITS` bits hold the kind, and the remaining bits hold the index into that kind's tracker.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedId32<const KIND_BITS: u32>(u32);
+
+/**
+An id which packs a small kind discriminant and an index into a single [u64], for references which may point at one of several kinds of entity.
+
+The highest `KIND_BITS` bits hold the kind, and the remaining bits hold the index into that kind's tracker.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedId64<const KIND_BITS: u32>(u64);
+
+impl <const KIND_BITS: u32> PackedId32<KIND_BITS> {
+    const INDEX_BITS: u32 = u32::BITS - KIND_BITS;
+    const VALID_KIND_BITS: () = assert!(KIND_BITS > 0 && KIND_BITS < u32::BITS, "PackedId32 must have between 1 and 31 kind bits");
+
+    /// Pack a kind and an index together, returning an [Err] if either is too large for its bits
+    pub fn new(kind: u32, index: usize) -> Result<Self,()> {
+        let () = Self::VALID_KIND_BITS;
+        if u64::from(kind) >> KIND_BITS != 0
+            { return Err(()) }
+        match u32::try_from(index) {
+            Ok(index) if index >> Self::INDEX_BITS == 0 => Ok(Self((kind << Self::INDEX_BITS) | index)),
+            _ => Err(()),
+        }
+    }
+    /// Get the kind discriminant of this id
+    pub fn kind(self) -> u32 { self.0 >> Self::INDEX_BITS }
+    /// Get the index of this id within its kind
+    pub fn index(self) -> usize { (self.0 & (u32::MAX >> KIND_BITS)) as usize }
+    /// Get the packed representation of this id
+    pub fn to_bits(self) -> u32 { self.0 }
+    /// Read an id from its packed representation
+    pub fn from_bits(bits: u32) -> Self {
+        let () = Self::VALID_KIND_BITS;
+        Self(bits)
+    }
+}
+
+impl <const KIND_BITS: u32> PackedId64<KIND_BITS> {
+    const INDEX_BITS: u32 = u64::BITS - KIND_BITS;
+    const VALID_KIND_BITS: () = assert!(KIND_BITS > 0 && KIND_BITS <= u32::BITS, "PackedId64 must have between 1 and 32 kind bits");
+
+    /// Pack a kind and an index together, returning an [Err] if either is too large for its bits
+    pub fn new(kind: u32, index: usize) -> Result<Self,()> {
+        let () = Self::VALID_KIND_BITS;
+        if u64::from(kind) >> KIND_BITS != 0
+            { return Err(()) }
+        match u64::try_from(index) {
+            Ok(index) if index >> Self::INDEX_BITS == 0 => Ok(Self((u64::from(kind) << Self::INDEX_BITS) | index)),
+            _ => Err(()),
+        }
+    }
+    /// Get the kind discriminant of this id
+    pub fn kind(self) -> u32 { (self.0 >> Self::INDEX_BITS) as u32 }
+    /// Get the index of this id within its kind
+    pub fn index(self) -> usize { (self.0 & (u64::MAX >> KIND_BITS)) as usize }
+    /// Get the packed representation of this id
+    pub fn to_bits(self) -> u64 { self.0 }
+    /// Read an id from its packed representation
+    pub fn from_bits(bits: u64) -> Self {
+        let () = Self::VALID_KIND_BITS;
+        Self(bits)
+    }
+}
+
+impl <const KIND_BITS: u32> Debug for PackedId32<KIND_BITS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackedId32").field("kind", &self.kind()).field("index", &self.index()).finish()
+    }
+}
+
+impl <const KIND_BITS: u32> Debug for PackedId64<KIND_BITS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackedId64").field("kind", &self.kind()).field("index", &self.index()).finish()
+    }
+}
+
+/**
+An id which is made up of a kind discriminant and an index, so that it can be routed by a [crate::trackers::PackedIdDispatcher].
+ */
+pub trait PackedId: Clone + Copy + PartialEq + Eq + Hash + Debug {
+    /// Pack a kind and an index together, returning an [Err] if either is too large for its bits
+    fn pack(kind: u32, index: usize) -> Result<Self,()>;
+    fn kind(self) -> u32;
+    fn index(self) -> usize;
+}
+
+impl <const KIND_BITS: u32> PackedId for PackedId32<KIND_BITS> {
+    fn pack(kind: u32, index: usize) -> Result<Self,()> { PackedId32::new(kind, index) }
+    fn kind(self) -> u32 { PackedId32::kind(self) }
+    fn index(self) -> usize { PackedId32::index(self) }
+}
+
+impl <const KIND_BITS: u32> PackedId for PackedId64<KIND_BITS> {
+    fn pack(kind: u32, index: usize) -> Result<Self,()> { PackedId64::new(kind, index) }
+    fn kind(self) -> u32 { PackedId64::kind(self) }
+    fn index(self) -> usize { PackedId64::index(self) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackedId32, PackedId64};
+
+    #[test]
+    fn packed_ids_hold_kinds_and_indexes_up_to_their_bit_limits() {
+        let largest = PackedId32::<4>::new(15, (1 << 28) - 1).unwrap();
+        assert_eq!((largest.kind(), largest.index()), (15, (1 << 28) - 1));
+        assert_eq!(largest.to_bits(), u32::MAX);
+        assert_eq!(PackedId32::<4>::new(16, 0), Err(()));
+        assert_eq!(PackedId32::<4>::new(0, 1 << 28), Err(()));
+        assert_eq!(PackedId32::<4>::new(3, 0).unwrap().to_bits(), 3 << 28);
+
+        // The most kind bits leave a single index bit
+        assert_eq!(PackedId32::<31>::new(0x7FFF_FFFF, 1).unwrap().to_bits(), u32::MAX);
+        assert_eq!(PackedId32::<31>::new(0, 2), Err(()));
+        assert_eq!(PackedId32::<1>::new(1, (1 << 31) - 1).unwrap().to_bits(), u32::MAX);
+        assert_eq!(PackedId32::<1>::new(2, 0), Err(()));
+    }
+
+    #[test]
+    fn wide_packed_ids_hold_kinds_and_indexes_up_to_their_bit_limits() {
+        let largest = PackedId64::<32>::new(u32::MAX, u32::MAX as usize).unwrap();
+        assert_eq!((largest.kind(), largest.index()), (u32::MAX, u32::MAX as usize));
+        assert_eq!(PackedId64::<32>::new(0, 1 << 32), Err(()));
+        let largest = PackedId64::<8>::new(255, (1 << 56) - 1).unwrap();
+        assert_eq!(largest.to_bits(), u64::MAX);
+        assert_eq!(PackedId64::<8>::new(256, 0), Err(()));
+        assert_eq!(PackedId64::<8>::new(0, 1 << 56), Err(()));
+    }
+
+    #[test]
+    fn packed_ids_round_trip_through_their_bits() {
+        let id = PackedId64::<8>::new(7, 123_456).unwrap();
+        assert_eq!(PackedId64::<8>::from_bits(id.to_bits()), id);
+        assert_eq!(PackedId32::<4>::from_bits(0x2000_0005).kind(), 2);
+        assert_eq!(PackedId32::<4>::from_bits(0x2000_0005).index(), 5);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::num::TryFromIntError;
+use std::str::FromStr;
+
+use crate::Identifier;
+
+/// The character placed between the prefix of a [Prefixed] id and the id itself
+pub const PREFIX_SEPARATOR: char = '_';
+
+/**
+A marker type which gives a [Prefixed] id its domain prefix.
+
+For example, a prefix of `"cus"` would cause ids to be written like `cus_4F2A`.
+ */
+pub trait IdPrefix {
+    /// The prefix which ids of this domain are written with, not including the [PREFIX_SEPARATOR]
+    const PREFIX: &'static str;
+}
+
+/**
+A reason that a string could not be parsed into a [Prefixed] id.
+ */
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum PrefixedParseError<E> {
+    /// The string did not start with the expected prefix and separator
+    WrongPrefix { expected: &'static str, found: String },
+    /// The prefix was correct, but the rest of the string could not be parsed into an id
+    Inner(E),
+}
+
+impl <E: Display> Display for PrefixedParseError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongPrefix { expected, found } => write!(f, "expected an id prefixed with {:?}, found {:?}", expected, found),
+            Self::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl <E: Debug + Display> std::error::Error for PrefixedParseError<E> {}
+
+/**
+An id which is written with a compile-time domain prefix, such as `user_01H4`.
+
+Parsing checks the prefix, so an `order_` id can never be parsed as a `user_` id.
+If the wrapped id is an [Identifier], then so is the [Prefixed] id.
+ */
+pub struct Prefixed<P: IdPrefix, I> {
+    id: I,
+    p: PhantomData<fn() -> P>,
+}
+
+impl <P: IdPrefix, I> Prefixed<P,I> {
+    /// Wrap an id in this prefix
+    pub fn new(id: I) -> Self { Self { id, p: PhantomData } }
+    /// Get the wrapped id
+    pub fn id(&self) -> &I { &self.id }
+    /// Unwrap the id, discarding the prefix
+    pub fn into_inner(self) -> I { self.id }
+}
+
+impl <P: IdPrefix, I: Display> Display for Prefixed<P,I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", P::PREFIX, PREFIX_SEPARATOR, self.id)
+    }
+}
+
+impl <P: IdPrefix, I: FromStr> FromStr for Prefixed<P,I> {
+    type Err = PrefixedParseError<I::Err>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = match s.strip_prefix(P::PREFIX).and_then(|rest| rest.strip_prefix(PREFIX_SEPARATOR)) {
+            Some(rest) => rest,
+            None => return Err(PrefixedParseError::WrongPrefix { expected: P::PREFIX, found: s.to_string() }),
+        }; match rest.parse() {
+            Ok(id) => Ok(Self::new(id)),
+            Err(err) => Err(PrefixedParseError::Inner(err)),
+        }
+    }
+}
+
+impl <P: IdPrefix, I: Identifier> Identifier for Prefixed<P,I> {
+    fn first() -> Self { Self::new(I::first()) }
+    fn next(self) -> Self { Self::new(self.id.next()) }
+}
+
+impl <P: IdPrefix, I: Identifier> TryFrom<usize> for Prefixed<P,I> {
+    type Error = TryFromIntError;
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match I::try_from(value) {
+            Ok(id) => Ok(Self::new(id)),
+            Err(err) => Err(err),
+        }
+    }
+}
+impl <P: IdPrefix, I: Identifier> TryFrom<Prefixed<P,I>> for usize {
+    type Error = TryFromIntError;
+    fn try_from(value: Prefixed<P,I>) -> Result<Self, Self::Error> {
+        value.id.try_into()
+    }
+}
+
+impl <P: IdPrefix, I: Clone> Clone for Prefixed<P,I> {
+    fn clone(&self) -> Self { Self::new(self.id.clone()) }
+}
+impl <P: IdPrefix, I: Copy> Copy for Prefixed<P,I> {}
+impl <P: IdPrefix, I: PartialEq> PartialEq for Prefixed<P,I> {
+    fn eq(&self, other: &Self) -> bool { self.id == other.id }
+}
+impl <P: IdPrefix, I: Eq> Eq for Prefixed<P,I> {}
+impl <P: IdPrefix, I: PartialOrd> PartialOrd for Prefixed<P,I> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { self.id.partial_cmp(&other.id) }
+}
+impl <P: IdPrefix, I: Ord> Ord for Prefixed<P,I> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.id.cmp(&other.id) }
+}
+impl <P: IdPrefix, I: Hash> Hash for Prefixed<P,I> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) { self.id.hash(state) }
+}
+impl <P: IdPrefix, I: Debug> Debug for Prefixed<P,I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Prefixed").field(&P::PREFIX).field(&self.id).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <P: IdPrefix, I: Display> serde::Serialize for Prefixed<P,I> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, P: IdPrefix, I: FromStr> serde::Deserialize<'de> for Prefixed<P,I> where I::Err: Display {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdPrefix, Prefixed, PrefixedParseError};
+    use crate::{Id32, Id64};
+
+    struct Users;
+    impl IdPrefix for Users { const PREFIX: &'static str = "user"; }
+    struct Orders;
+    impl IdPrefix for Orders { const PREFIX: &'static str = "order"; }
+
+    #[test]
+    fn display_and_parse_round_trip() {
+        for n in [0, 1, 1234, u64::MAX] {
+            let id = Prefixed::<Users,Id64>::new(Id64(n));
+            let written = id.to_string();
+            assert_eq!(written, format!("user_{}", Id64(n)));
+            assert_eq!(written.parse::<Prefixed<Users,Id64>>(), Ok(id));
+        }
+    }
+
+    #[test]
+    fn parsing_checks_the_prefix() {
+        let written = Prefixed::<Orders,Id32>::new(Id32(5)).to_string();
+        assert_eq!(written.parse::<Prefixed<Users,Id32>>(), Err(PrefixedParseError::WrongPrefix { expected: "user", found: written.clone() }));
+        // The prefix must be followed by the separator, not just start the string
+        assert!(matches!("users5".parse::<Prefixed<Users,Id32>>(), Err(PrefixedParseError::WrongPrefix { .. })));
+        assert!(matches!("user_".parse::<Prefixed<Users,Id32>>(), Err(PrefixedParseError::Inner(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips_through_the_prefixed_string() {
+        let id = Prefixed::<Users,Id64>::new(Id64(987_654));
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("\"{}\"", id));
+        assert_eq!(serde_json::from_str::<Prefixed<Users,Id64>>(&json).unwrap(), id);
+        assert!(serde_json::from_str::<Prefixed<Users,Id64>>(&serde_json::to_string(&Prefixed::<Orders,Id64>::new(Id64(1))).unwrap()).is_err());
+    }
+}
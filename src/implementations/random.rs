@@ -0,0 +1,42 @@
+use crate::base::RandomIdImpl;
+
+use super::{Id8, Id16, Id32, Id64, Id128};
+
+/// Fill a buffer from the operating system's cryptographically secure random number generator
+fn fill_random<const N: usize>() -> Result<[u8; N],()> {
+    let mut bytes = [0u8; N];
+    match getrandom::getrandom(&mut bytes) {
+        Ok(()) => Ok(bytes),
+        Err(_) => Err(()),
+    }
+}
+
+impl RandomIdImpl for Id8 {
+    fn random() -> Result<Self,()> {
+        fill_random().map(|bytes| Self(u8::from_ne_bytes(bytes)))
+    }
+}
+
+impl RandomIdImpl for Id16 {
+    fn random() -> Result<Self,()> {
+        fill_random().map(|bytes| Self(u16::from_ne_bytes(bytes)))
+    }
+}
+
+impl RandomIdImpl for Id32 {
+    fn random() -> Result<Self,()> {
+        fill_random().map(|bytes| Self(u32::from_ne_bytes(bytes)))
+    }
+}
+
+impl RandomIdImpl for Id64 {
+    fn random() -> Result<Self,()> {
+        fill_random().map(|bytes| Self(u64::from_ne_bytes(bytes)))
+    }
+}
+
+impl RandomIdImpl for Id128 {
+    fn random() -> Result<Self,()> {
+        fill_random().map(|bytes| Self(u128::from_ne_bytes(bytes)))
+    }
+}
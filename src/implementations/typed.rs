@@ -0,0 +1,159 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::num::TryFromIntError;
+use std::str::FromStr;
+
+use crate::{IdImpl, Identifier, RandomIdImpl, RandomIdentifier, KeyBytes};
+use crate::errors::RANDOM_ID_ERROR;
+
+const OUT_OF_IDS_ERROR: &str = "Ids: ran out of ids for a TypedId";
+
+/**
+An [Identifier] for the domain `D`, backed by the [IdImpl] `T`.
+
+The domain is a marker type which is never constructed, so this is exactly as large as `T`.
+Ids of different domains are different types, so an id for one tracker, linker or [crate::UpdatableIdStore]
+cannot be used to query another, even when both use the same [IdImpl].
+```
+use ids::{Id32, Identifier, TypedId};
+
+struct Users; struct Orders;
+type UserId = TypedId<Users, Id32>;
+type OrderId = TypedId<Orders, Id32>;
+
+let user = UserId::first().next();
+let order = OrderId::new(user.inner());
+assert_eq!(std::mem::size_of::<UserId>(), std::mem::size_of::<Id32>());
+assert_eq!(user.inner(), order.inner());
+```
+Comparing ids of different domains does not compile:
+```compile_fail
+use ids::{Id32, Identifier, TypedId};
+
+struct Users; struct Orders;
+let same = TypedId::<Users, Id32>::first() == TypedId::<Orders, Id32>::first();
+```
+ */
+pub struct TypedId<D, T: IdImpl> {
+    inner: T,
+    d: PhantomData<fn() -> D>,
+}
+
+impl <D, T: IdImpl> TypedId<D,T> {
+    /// Wrap an untyped id in this domain
+    pub fn new(inner: T) -> Self { Self { inner, d: PhantomData } }
+    /// Get the untyped id, discarding the domain
+    pub fn inner(self) -> T { self.inner }
+}
+
+impl <D, T: IdImpl + Debug> Identifier for TypedId<D,T> {
+    fn first() -> Self { Self::new(T::first()) }
+    fn next(self) -> Self {
+        match self.inner.next() {
+            Ok(next) => Self::new(next),
+            Err(()) => panic!("{}", OUT_OF_IDS_ERROR),
+        }
+    }
+}
+
+impl <D, T: RandomIdImpl + Debug> RandomIdentifier for TypedId<D,T> {
+    fn random() -> Self {
+        match T::random() {
+            Ok(inner) => Self::new(inner),
+            Err(()) => panic!("{}", RANDOM_ID_ERROR),
+        }
+    }
+}
+
+impl <D, T: IdImpl> TryFrom<usize> for TypedId<D,T> {
+    type Error = TryFromIntError;
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match T::try_from(value) {
+            Ok(inner) => Ok(Self::new(inner)),
+            Err(err) => Err(err),
+        }
+    }
+}
+impl <D, T: IdImpl> TryFrom<TypedId<D,T>> for usize {
+    type Error = TryFromIntError;
+    fn try_from(value: TypedId<D,T>) -> Result<Self, Self::Error> {
+        value.inner.try_into()
+    }
+}
+
+impl <D, T: IdImpl + Display> Display for TypedId<D,T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}", self.inner) }
+}
+impl <D, T: IdImpl + FromStr> FromStr for TypedId<D,T> {
+    type Err = T::Err;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(inner) => Ok(Self::new(inner)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl <D, T: IdImpl + KeyBytes> KeyBytes for TypedId<D,T> {
+    const KEY_LEN: usize = T::KEY_LEN;
+    fn write_key_bytes(&self, out: &mut Vec<u8>) { self.inner.write_key_bytes(out) }
+    fn read_key_bytes(bytes: &[u8]) -> Self { Self::new(T::read_key_bytes(bytes)) }
+}
+
+impl <D, T: IdImpl> Clone for TypedId<D,T> {
+    fn clone(&self) -> Self { *self }
+}
+impl <D, T: IdImpl> Copy for TypedId<D,T> {}
+impl <D, T: IdImpl> PartialEq for TypedId<D,T> {
+    fn eq(&self, other: &Self) -> bool { self.inner == other.inner }
+}
+impl <D, T: IdImpl> Eq for TypedId<D,T> {}
+impl <D, T: IdImpl + PartialOrd> PartialOrd for TypedId<D,T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { self.inner.partial_cmp(&other.inner) }
+}
+impl <D, T: IdImpl + Ord> Ord for TypedId<D,T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.inner.cmp(&other.inner) }
+}
+impl <D, T: IdImpl> Hash for TypedId<D,T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) { self.inner.hash(state) }
+}
+impl <D, T: IdImpl + Debug> Debug for TypedId<D,T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TypedId<{}>({:?})", std::any::type_name::<D>(), self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypedId;
+    use crate::{Id8, Id32, IdImpl, Identifier, KeyBytes};
+
+    struct Users;
+    type UserId = TypedId<Users, Id32>;
+
+    #[test]
+    fn typed_ids_count_like_their_inner_ids() {
+        assert_eq!(UserId::first().inner(), Id32::first());
+        assert_eq!(UserId::first().next().inner(), Id32::first().next().unwrap());
+        assert_eq!(UserId::try_from(7usize), Ok(UserId::new(Id32(7))));
+        assert_eq!(usize::try_from(UserId::new(Id32(7))), Ok(7));
+        assert!(TypedId::<Users, Id8>::try_from(256usize).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "ran out of ids")]
+    fn typed_ids_panic_when_they_run_out() {
+        TypedId::<Users, Id8>::new(Id8(u8::MAX)).next();
+    }
+
+    #[test]
+    fn typed_ids_are_written_like_their_inner_ids() {
+        let id = UserId::new(Id32(123_456));
+        assert_eq!(id.to_string(), Id32(123_456).to_string());
+        assert_eq!(id.to_string().parse::<UserId>(), Ok(id));
+        assert_eq!(id.to_key_bytes(), Id32(123_456).to_key_bytes());
+        assert_eq!(UserId::from_key_bytes(&id.to_key_bytes()), Ok(id));
+        assert!(format!("{:?}", id).contains("Users"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::num::TryFromIntError;
+
+use crate::IdDecodeError;
+use crate::errors::CONVERT_TO_USIZE_ERROR;
+
+use super::SizedIdImpl;
+
+/// Append the LEB128 varint encoding of an id to a buffer. Small ids take fewer bytes.
+pub fn encode_varint<T: SizedIdImpl>(id: T, out: &mut Vec<u8>) {
+    write_varint(id.to_u128(), out)
+}
+
+/**
+Read an id from the start of some LEB128 varint bytes.
+
+Returns the id along with the number of bytes which were read,
+or an [Err] if the bytes were cut short, describe too large an id, or are longer than the id needs.
+ */
+pub fn decode_varint<T: SizedIdImpl>(bytes: &[u8]) -> Result<(T,usize),IdDecodeError> {
+    let (value, read) = match read_varint(bytes) {
+        Ok(vals) => vals,
+        Err(err) => return Err(err),
+    }; match T::from_u128(value) {
+        Ok(id) => Ok((id, read)),
+        Err(_) => Err(IdDecodeError::Overflow),
+    }
+}
+
+/**
+Encode a list of ids compactly, by sorting them and writing the varint of the difference between each id and the last.
+
+This suits the output of [crate::trackers::IdTracker::ids], where neighbouring ids are close together.
+The order of the ids is not preserved, but duplicates are.
+Panics if an id cannot be converted to a [usize].
+ */
+pub fn encode_id_list<T, Itr>(ids: Itr) -> Vec<u8>
+where T: Copy + TryInto<usize,Error = TryFromIntError>, Itr: IntoIterator<Item = T> {
+    let mut values: Vec<usize> = ids.into_iter()
+        .map(|id| id.try_into().expect(CONVERT_TO_USIZE_ERROR))
+        .collect();
+    values.sort();
+    let mut out = Vec::new();
+    write_varint(values.len() as u128, &mut out);
+    let mut previous = 0;
+    for value in values {
+        write_varint((value - previous) as u128, &mut out);
+        previous = value;
+    }; return out;
+}
+
+/// Decode a list of ids written by [encode_id_list], in ascending order
+pub fn decode_id_list<T>(bytes: &[u8]) -> Result<Vec<T>,IdDecodeError>
+where T: TryFrom<usize,Error = TryFromIntError> {
+    let mut reader = VarintReader { bytes, position: 0 };
+    let count = match reader.next_usize() {
+        Ok(count) => count,
+        Err(err) => return Err(err),
+    };
+    let mut ids = Vec::new();
+    let mut previous: usize = 0;
+    for _ in 0..count {
+        previous = match reader.next_usize().map(|delta| previous.checked_add(delta)) {
+            Ok(Some(value)) => value,
+            Ok(None) => return Err(IdDecodeError::Overflow),
+            Err(err) => return Err(err),
+        }; match T::try_from(previous) {
+            Ok(id) => ids.push(id),
+            Err(_) => return Err(IdDecodeError::Overflow),
+        }
+    }; reader.finish().map(|()| ids)
+}
+
+/**
+Encode a mapping from old ids to new ids compactly, such as that returned by [crate::trackers::IdTracker::flatten].
+
+The old ids are delta-encoded in ascending order, and each new id is written as the signed difference from the previous new id.
+Panics if an id cannot be converted to a [usize].
+ */
+pub fn encode_id_mapping<T>(mapping: &HashMap<T,T>) -> Vec<u8>
+where T: Copy + TryInto<usize,Error = TryFromIntError> {
+    let mut pairs: Vec<(usize,usize)> = mapping.iter()
+        .map(|(old,new)| ((*old).try_into().expect(CONVERT_TO_USIZE_ERROR), (*new).try_into().expect(CONVERT_TO_USIZE_ERROR)))
+        .collect();
+    pairs.sort();
+    let mut out = Vec::new();
+    write_varint(pairs.len() as u128, &mut out);
+    let (mut previous_old, mut previous_new) = (0, 0);
+    for (old, new) in pairs {
+        write_varint((old - previous_old) as u128, &mut out);
+        write_varint(zigzag(new as i128 - previous_new as i128), &mut out);
+        (previous_old, previous_new) = (old, new);
+    }; return out;
+}
+
+/// Decode a mapping from old ids to new ids written by [encode_id_mapping]
+pub fn decode_id_mapping<T>(bytes: &[u8]) -> Result<HashMap<T,T>,IdDecodeError>
+where T: Hash + Eq + TryFrom<usize,Error = TryFromIntError> {
+    let mut reader = VarintReader { bytes, position: 0 };
+    let count = match reader.next_usize() {
+        Ok(count) => count,
+        Err(err) => return Err(err),
+    };
+    let mut mapping = HashMap::new();
+    let (mut previous_old, mut previous_new): (usize, usize) = (0, 0);
+    for _ in 0..count {
+        let (old_delta, new_delta) = match (reader.next_usize(), reader.next()) {
+            (Ok(old_delta), Ok(new_delta)) => (old_delta, unzigzag(new_delta)),
+            (Err(err), _) | (_, Err(err)) => return Err(err),
+        };
+        let old = match previous_old.checked_add(old_delta) {
+            Some(old) => old,
+            None => return Err(IdDecodeError::Overflow),
+        }; let new = match (previous_new as i128).checked_add(new_delta).map(usize::try_from) {
+            Some(Ok(new)) => new,
+            _ => return Err(IdDecodeError::Overflow),
+        }; match (T::try_from(old), T::try_from(new)) {
+            (Ok(old_id), Ok(new_id)) => { mapping.insert(old_id, new_id); },
+            _ => return Err(IdDecodeError::Overflow),
+        }; (previous_old, previous_new) = (old, new);
+    }; reader.finish().map(|()| mapping)
+}
+
+/// Append the LEB128 encoding of a value to a buffer
+fn write_varint(mut value: u128, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 { out.push(byte); break; }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Read a LEB128 value from the start of some bytes, returning it and the number of bytes read, and rejecting overlong encodings
+fn read_varint(bytes: &[u8]) -> Result<(u128,usize),IdDecodeError> {
+    let mut value: u128 = 0;
+    for (n, byte) in bytes.iter().enumerate() {
+        let shift = 7 * n as u32;
+        let bits = u128::from(byte & 0x7F);
+        if shift >= u128::BITS || (bits << shift) >> shift != bits
+            { return Err(IdDecodeError::Overflow) }
+        value |= bits << shift;
+        // A final zero group adds nothing, so only the single byte of zero itself may end with one
+        if *byte == 0 && n > 0
+            { return Err(IdDecodeError::LeadingZero) }
+        if byte & 0x80 == 0 { return Ok((value, n + 1)) }
+    }; return Err(IdDecodeError::WrongLength { expected: bytes.len() + 1, found: bytes.len() });
+}
+
+fn zigzag(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+fn unzigzag(value: u128) -> i128 {
+    (value >> 1) as i128 ^ -((value & 1) as i128)
+}
+
+/// A cursor over a sequence of varints
+struct VarintReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl VarintReader<'_> {
+    fn next(&mut self) -> Result<u128,IdDecodeError> {
+        match read_varint(&self.bytes[self.position..]) {
+            Ok((value, read)) => { self.position += read; Ok(value) },
+            Err(err) => Err(err),
+        }
+    }
+
+    fn next_usize(&mut self) -> Result<usize,IdDecodeError> {
+        match self.next() {
+            Ok(value) => usize::try_from(value).map_err(|_| IdDecodeError::Overflow),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check that every byte was read
+    fn finish(&self) -> Result<(),IdDecodeError> {
+        if self.position == self.bytes.len() { Ok(()) }
+        else { Err(IdDecodeError::WrongLength { expected: self.position, found: self.bytes.len() }) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{decode_id_list, decode_id_mapping, decode_varint, encode_id_list, encode_id_mapping, encode_varint};
+    use crate::{IdDecodeError, Id8, Id32, Id64, Id128};
+
+    #[test]
+    fn varints_encode_known_values() {
+        let mut out = Vec::new();
+        encode_varint(Id32(0), &mut out);
+        encode_varint(Id32(127), &mut out);
+        encode_varint(Id32(300), &mut out);
+        assert_eq!(out, vec![0x00, 0x7F, 0xAC, 0x02]);
+        assert_eq!(decode_varint::<Id32>(&out[2..]), Ok((Id32(300), 2)));
+    }
+
+    #[test]
+    fn varints_round_trip_extreme_values() {
+        for value in [0, 1, u128::from(u64::MAX), u128::MAX] {
+            let mut out = Vec::new();
+            encode_varint(Id128(value), &mut out);
+            assert_eq!(decode_varint::<Id128>(&out), Ok((Id128(value), out.len())));
+        }
+        let mut out = Vec::new();
+        encode_varint(Id64(u64::MAX), &mut out);
+        assert_eq!(out.len(), 10);
+        assert_eq!(decode_varint::<Id64>(&out), Ok((Id64(u64::MAX), 10)));
+    }
+
+    #[test]
+    fn varints_reject_truncated_and_oversized_input() {
+        assert_eq!(decode_varint::<Id32>(&[0xAC]), Err(IdDecodeError::WrongLength { expected: 2, found: 1 }));
+        assert_eq!(decode_varint::<Id32>(&[]), Err(IdDecodeError::WrongLength { expected: 1, found: 0 }));
+        assert_eq!(decode_varint::<Id8>(&[0xAC, 0x02]), Err(IdDecodeError::Overflow));
+        // Twenty continuation bytes describe more than 128 bits
+        let mut too_long = vec![0xFF; 19];
+        too_long.push(0x01);
+        assert_eq!(decode_varint::<Id128>(&too_long), Err(IdDecodeError::Overflow));
+    }
+
+    #[test]
+    fn varints_reject_overlong_encodings() {
+        assert_eq!(decode_varint::<Id32>(&[0x80, 0x00]), Err(IdDecodeError::LeadingZero));
+        assert_eq!(decode_varint::<Id32>(&[0xAC, 0x82, 0x00]), Err(IdDecodeError::LeadingZero));
+        assert_eq!(decode_varint::<Id32>(&[0x00, 0x00]), Ok((Id32(0), 1)));
+        assert_eq!(decode_id_list::<Id32>(&[0x01, 0x85, 0x00]), Err(IdDecodeError::LeadingZero));
+    }
+
+    #[test]
+    fn id_lists_round_trip_sorted_with_duplicates() {
+        let ids = vec![Id32(40), Id32(3), Id32(1_000_000), Id32(3), Id32(0)];
+        let encoded = encode_id_list(ids);
+        assert_eq!(decode_id_list::<Id32>(&encoded), Ok(vec![Id32(0), Id32(3), Id32(3), Id32(40), Id32(1_000_000)]));
+        assert_eq!(decode_id_list::<Id32>(&encode_id_list(Vec::<Id32>::new())), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn id_lists_are_smaller_than_fixed_width() {
+        let ids: Vec<Id64> = (1000..2000).map(Id64).collect();
+        assert!(encode_id_list(ids).len() < 1000 * 2);
+    }
+
+    #[test]
+    fn id_lists_reject_trailing_and_missing_bytes() {
+        let mut encoded = encode_id_list(vec![Id32(1), Id32(2)]);
+        encoded.push(0);
+        assert!(matches!(decode_id_list::<Id32>(&encoded), Err(IdDecodeError::WrongLength { .. })));
+        encoded.truncate(encoded.len() - 2);
+        assert!(matches!(decode_id_list::<Id32>(&encoded), Err(IdDecodeError::WrongLength { .. })));
+        assert_eq!(decode_id_list::<Id8>(&encode_id_list(vec![Id32(256)])), Err(IdDecodeError::Overflow));
+    }
+
+    #[test]
+    fn id_mappings_round_trip_when_new_ids_decrease() {
+        let mapping: HashMap<Id32,Id32> = [(0, 0), (5, 1), (9, 2), (10, 0), (700, 3)].into_iter()
+            .map(|(old, new)| (Id32(old), Id32(new)))
+            .collect();
+        assert_eq!(decode_id_mapping::<Id32>(&encode_id_mapping(&mapping)), Ok(mapping));
+        assert_eq!(decode_id_mapping::<Id32>(&encode_id_mapping(&HashMap::<Id32,Id32>::new())), Ok(HashMap::new()));
+    }
+}
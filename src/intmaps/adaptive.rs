@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use super::{DenseIntMap, IntMap, RefIntMap, SparseIntMap};
+
+/// Below this many keys, an [AdaptiveIntMap] keeps whichever representation it already has
+const MIN_ADAPTIVE_SPAN: usize = 64;
+
+enum Representation<V: Clone> {
+    Dense(DenseIntMap<V>),
+    Sparse(SparseIntMap<V>),
+}
+
+/**
+An [IntMap] which stores its values like a [DenseIntMap] while most keys are in use, and like a [SparseIntMap] while few are.
+
+Occupancy is the number of values stored, divided by the number of keys handed out so far.
+The map becomes sparse when occupancy falls below one threshold, and dense again when it rises above another.
+Converting between representations never changes the key of any value.
+ */
+pub struct AdaptiveIntMap<V: Clone> {
+    repr: Representation<V>,
+    counter: usize,
+    len: usize,
+    sparse_below: f64,
+    dense_above: f64,
+}
+
+impl <V: Clone> AdaptiveIntMap<V> {
+    /**
+    Create a map which becomes sparse when its occupancy falls below `sparse_below`, and dense when it rises above `dense_above`.
+
+    Returns an [Err] unless `0 <= sparse_below <= dense_above <= 1`, since otherwise the map could switch back and forth on every change.
+     */
+    pub fn with_thresholds(sparse_below: f64, dense_above: f64) -> Result<Self,()> {
+        if !(0.0 <= sparse_below && sparse_below <= dense_above && dense_above <= 1.0)
+            { return Err(()) }
+        return Ok(Self { repr: Representation::Dense(DenseIntMap::default()), counter: 0, len: 0, sparse_below, dense_above });
+    }
+
+    /// Whether this map is currently using the dense representation
+    pub fn is_dense(&self) -> bool { matches!(self.repr, Representation::Dense(_)) }
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    fn inner(&self) -> &dyn IntMap<V> {
+        match &self.repr {
+            Representation::Dense(map) => map,
+            Representation::Sparse(map) => map,
+        }
+    }
+
+    fn contains_key(&self, k: usize) -> bool {
+        match &self.repr {
+            Representation::Dense(map) => map.contains_key(k),
+            Representation::Sparse(map) => map.contains_key(k),
+        }
+    }
+
+    fn set(&mut self, k: usize, elem: Option<V>) {
+        match (self.contains_key(k), elem.is_some()) {
+            (false, true) => self.len += 1,
+            (true, false) => self.len -= 1,
+            (false, false) => return,
+            (true, true) => {},
+        }
+        // Switch before storing, so that a distant key is never stored densely only to be converted straight away
+        self.adapt();
+        match &mut self.repr {
+            Representation::Dense(map) => map.put(k, elem),
+            Representation::Sparse(map) => map.put(k, elem),
+        }
+    }
+
+    /// Switch representation if the occupancy that [AdaptiveIntMap::len] and the counter describe has crossed a threshold
+    fn adapt(&mut self) {
+        if self.counter < MIN_ADAPTIVE_SPAN
+            { return }
+        let occupancy = self.len as f64 / self.counter as f64;
+        let repr = std::mem::replace(&mut self.repr, Representation::Sparse(SparseIntMap::default()));
+        self.repr = match repr {
+            Representation::Dense(map) if occupancy < self.sparse_below => {
+                let mut sparse = SparseIntMap::default();
+                for (k,v) in map.into_entries()
+                    { sparse.put(k, Some(v)); }
+                Representation::Sparse(sparse)
+            },
+            Representation::Sparse(map) if occupancy > self.dense_above => {
+                let mut dense = DenseIntMap::default();
+                for (k,v) in map.into_entries()
+                    { dense.put(k, Some(v)); }
+                Representation::Dense(dense)
+            },
+            repr => repr,
+        };
+    }
+}
+
+impl <V: Clone> IntMap<V> for AdaptiveIntMap<V> {
+    fn add(&mut self, v: V) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        self.set(id, Some(v));
+        return id;
+    }
+
+    fn reserve(&mut self) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        return id;
+    }
+
+    fn put(&mut self, k: usize, elem: Option<V>) {
+        if elem.is_some() && k >= self.counter
+            { self.counter = k+1 };
+        self.set(k, elem);
+    }
+
+    fn get(&self, k: usize) -> Option<V> { self.inner().get(k) }
+
+    fn keys(&self) -> Vec<usize> { self.inner().keys() }
+
+    fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized {
+        // A flattened map has no gaps, so it is always dense
+        let mut flattened = Self { repr: Representation::Dense(DenseIntMap::default()), counter: 0, len: 0, sparse_below: self.sparse_below, dense_above: self.dense_above };
+        let mut mapping = HashMap::new();
+        for k in self.keys() {
+            let v = self.get(k).expect("Ids: a key of an adaptive map had no value");
+            mapping.insert(k, flattened.add(v));
+        }
+        Ok((flattened, mapping))
+    }
+}
+
+impl <V: Clone> Default for AdaptiveIntMap<V> {
+    fn default() -> Self {
+        Self::with_thresholds(0.25, 0.5).expect("Ids: the default thresholds of an AdaptiveIntMap were invalid")
+    }
+}
+
+impl <V: Clone> RefIntMap<V> for AdaptiveIntMap<V> {
+    fn get_ref(&self, k: usize) -> Option<&V> {
+        match &self.repr {
+            Representation::Dense(map) => map.get_ref(k),
+            Representation::Sparse(map) => map.get_ref(k),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdaptiveIntMap;
+    use crate::intmaps::IntMap;
+
+    #[test]
+    fn becomes_sparse_when_emptied_and_dense_when_refilled() {
+        let mut map = AdaptiveIntMap::default();
+        for n in 0..100
+            { map.add(n); }
+        assert!(map.is_dense());
+        for k in 0..80
+            { map.rmv(k); }
+        assert!(!map.is_dense());
+        assert_eq!(map.len(), 20);
+        assert_eq!(map.keys(), (80..100).collect::<Vec<_>>());
+        for k in 0..80
+            { map.put(k, Some(k * 2)); }
+        assert!(map.is_dense());
+        assert_eq!((map.get(0), map.get(79), map.get(80), map.get(99)), (Some(0), Some(158), Some(80), Some(99)));
+    }
+
+    #[test]
+    fn distant_keys_are_stored_sparsely_without_growing_a_dense_map() {
+        let mut map = AdaptiveIntMap::<u8>::default();
+        map.put(1 << 40, Some(1));
+        assert!(!map.is_dense());
+        assert_eq!(map.get(1 << 40), Some(1));
+        assert_eq!(map.add(2), (1 << 40) + 1);
+        assert_eq!(map.keys(), vec![1 << 40, (1 << 40) + 1]);
+    }
+
+    #[test]
+    fn small_maps_keep_their_representation() {
+        let mut map = AdaptiveIntMap::default();
+        for n in 0..10
+            { map.add(n); }
+        for k in 0..9
+            { map.rmv(k); }
+        assert!(map.is_dense());
+        assert_eq!(map.keys(), vec![9]);
+    }
+
+    #[test]
+    fn thresholds_must_be_ordered_within_zero_and_one() {
+        assert!(AdaptiveIntMap::<u8>::with_thresholds(0.5, 0.25).is_err());
+        assert!(AdaptiveIntMap::<u8>::with_thresholds(-0.1, 0.5).is_err());
+        assert!(AdaptiveIntMap::<u8>::with_thresholds(0.5, 1.5).is_err());
+        assert!(AdaptiveIntMap::<u8>::with_thresholds(0.5, 0.5).is_ok());
+    }
+
+    #[test]
+    fn flattening_is_dense_and_keeps_order() {
+        let mut map = AdaptiveIntMap::default();
+        map.put(5, Some("a"));
+        map.put(1 << 20, Some("b"));
+        let (flattened, mapping) = map.get_flattening().unwrap();
+        assert!(flattened.is_dense());
+        assert_eq!((flattened.get(0), flattened.get(1)), (Some("a"), Some("b")));
+        assert_eq!((mapping[&5], mapping[&(1 << 20)]), (0, 1));
+    }
+}
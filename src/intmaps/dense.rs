@@ -1,4 +1,4 @@
-use super::IntMap;
+use super::{IntMap, RefIntMap};
 use std::{collections::HashMap};
 
 pub struct DenseIntMap<V: Clone> {
@@ -9,15 +9,26 @@ pub struct DenseIntMap<V: Clone> {
 impl <V: Clone> DenseIntMap<V> {
     fn set(&mut self, index: usize, elem: Option<V>) {
         if index >= self.inner.len()
-            { self.inner.resize(index,None); }
+            { self.inner.resize(index+1,None); }
         self.inner[index] = elem;
     }
+
+    pub(super) fn contains_key(&self, k: usize) -> bool { matches!(self.inner.get(k), Some(Some(_))) }
+    pub(super) fn into_entries(self) -> impl Iterator<Item = (usize,V)> {
+        self.inner.into_iter().enumerate().filter_map(|(n,v)| v.map(|v| (n,v)))
+    }
 }
 
 impl <V: Clone> IntMap<V> for DenseIntMap<V> {
     fn add(&mut self, v: V) -> usize {
         let id = self.counter;
-        self.inner.insert(id, Some(v));
+        self.set(id, Some(v));
+        self.counter += 1;
+        return id;
+    }
+
+    fn reserve(&mut self) -> usize {
+        let id = self.counter;
         self.counter += 1;
         return id;
     }
@@ -37,6 +48,12 @@ impl <V: Clone> IntMap<V> for DenseIntMap<V> {
         }
     }
 
+    fn keys(&self) -> Vec<usize> {
+        self.inner.iter().enumerate()
+            .filter_map(|(n,v)| v.as_ref().map(|_| n))
+            .collect()
+    }
+
     fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized {
         // Initialise mapping and flattened vector
         let mut flattened = Vec::new();
@@ -69,4 +86,13 @@ impl <V: Clone> Default for DenseIntMap<V> {
     fn default() -> Self {
         Self { inner: Default::default(), counter: Default::default() }
     }
-}
\ No newline at end of file
+}
+
+impl <V: Clone> RefIntMap<V> for DenseIntMap<V> {
+    fn get_ref(&self, k: usize) -> Option<&V> {
+        match self.inner.get(k) {
+            Some(v) => v.as_ref(),
+            None => None,
+        }
+    }
+}
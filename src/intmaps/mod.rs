@@ -1,14 +1,27 @@
 mod sparse;
 mod dense;
+mod sparse_set;
+mod paged;
+mod ordered;
+mod adaptive;
 
 use std::collections::HashMap;
 pub use dense::DenseIntMap;
+pub use sparse::SparseIntMap;
+pub use sparse_set::SparseSetIntMap;
+pub use paged::{PagedIntMap, DEFAULT_PAGE_SIZE};
+pub use ordered::OrderedIntMap;
+pub use adaptive::AdaptiveIntMap;
 
 pub trait IntMap<V> {
     fn add(&mut self, elem: V) -> usize;
+    /// Get a key which [IntMap::add] will never return, without storing anything under it yet
+    fn reserve(&mut self) -> usize;
     fn rmv(&mut self, k: usize) { self.put(k,None); }
     fn put(&mut self, k: usize, elem: Option<V>);
     fn get(&self, k: usize) -> Option<V>;
+    /// Get every key which currently has a value, in ascending order
+    fn keys(&self) -> Vec<usize>;
 
     /**
     Get what the result would be if this tracker were flattened (see the flatten() operation)
@@ -18,3 +31,11 @@ pub trait IntMap<V> {
      */
     fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized;
 }
+
+/**
+An [IntMap] which can lend out its values, rather than only cloning them.
+ */
+pub trait RefIntMap<V>: IntMap<V> {
+    /// Get a reference to the value under a key, if there is one
+    fn get_ref(&self, k: usize) -> Option<&V>;
+}
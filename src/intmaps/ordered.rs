@@ -0,0 +1,128 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeBounds;
+
+use super::{IntMap, RefIntMap};
+
+/**
+An [IntMap] which keeps its keys in order, allowing range queries and lookups of neighbouring keys.
+ */
+pub struct OrderedIntMap<V> {
+    inner: BTreeMap<usize,V>,
+    counter: usize,
+}
+
+impl <V> OrderedIntMap<V> {
+    fn set(&mut self, k: usize, elem: Option<V>) {
+        match elem {
+            Some(v) => { self.inner.insert(k,v); },
+            None => { self.inner.remove(&k); },
+        }
+    }
+
+    /// Iterate over every key and value in this map whose key is within a range, in ascending order of key
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> impl DoubleEndedIterator<Item = (usize, &V)> {
+        self.inner.range(range).map(|(k,v)| (*k,v))
+    }
+
+    /// Iterate over every key and value in this map, in ascending order of key
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &V)> { self.range(..) }
+
+    /// Get the value with the smallest key
+    pub fn first(&self) -> Option<(usize, &V)> { self.inner.first_key_value().map(|(k,v)| (*k,v)) }
+    /// Get the value with the largest key
+    pub fn last(&self) -> Option<(usize, &V)> { self.inner.last_key_value().map(|(k,v)| (*k,v)) }
+    /// Get the value with the largest key which is smaller than the one given
+    pub fn predecessor(&self, k: usize) -> Option<(usize, &V)> { self.range(..k).next_back() }
+    /// Get the value with the smallest key which is larger than the one given
+    pub fn successor(&self, k: usize) -> Option<(usize, &V)> {
+        match k.checked_add(1) {
+            Some(next) => self.range(next..).next(),
+            None => None,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.inner.len() }
+    pub fn is_empty(&self) -> bool { self.inner.is_empty() }
+}
+
+impl <V: Clone> IntMap<V> for OrderedIntMap<V> {
+    fn add(&mut self, v: V) -> usize {
+        let id = self.counter;
+        self.set(id, Some(v));
+        self.counter += 1;
+        return id;
+    }
+
+    fn reserve(&mut self) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        return id;
+    }
+
+    fn put(&mut self, k: usize, elem: Option<V>) {
+        if let Some(v) = elem {
+            self.set(k, Some(v));
+            if k >= self.counter
+                { self.counter = k+1 };
+        } else { self.set(k, None); }
+    }
+
+    fn get(&self, k: usize) -> Option<V> { self.inner.get(&k).cloned() }
+
+    fn keys(&self) -> Vec<usize> { self.inner.keys().copied().collect() }
+
+    fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized {
+        let mut flattened = Self::default();
+        let mut mapping = HashMap::new();
+        for (k,v) in self.iter()
+            { mapping.insert(k, flattened.add(v.clone())); }
+        Ok((flattened, mapping))
+    }
+}
+
+impl <V> Default for OrderedIntMap<V> {
+    fn default() -> Self {
+        Self { inner: BTreeMap::new(), counter: 0 }
+    }
+}
+
+impl <V: Clone> RefIntMap<V> for OrderedIntMap<V> {
+    fn get_ref(&self, k: usize) -> Option<&V> { self.inner.get(&k) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrderedIntMap;
+    use crate::intmaps::IntMap;
+
+    fn map_of(keys: &[usize]) -> OrderedIntMap<usize> {
+        let mut map = OrderedIntMap::default();
+        for k in keys
+            { map.put(*k, Some(*k * 10)); }
+        return map;
+    }
+
+    #[test]
+    fn ranges_are_in_ascending_order() {
+        let map = map_of(&[8, 2, 5, 11]);
+        assert_eq!(map.range(3..=8).collect::<Vec<_>>(), vec![(5, &50), (8, &80)]);
+        assert_eq!(map.range(..5).collect::<Vec<_>>(), vec![(2, &20)]);
+        assert_eq!(map.range(9..).rev().collect::<Vec<_>>(), vec![(11, &110)]);
+        assert_eq!(map.range(12..).next(), None);
+        assert_eq!((map.first(), map.last()), (Some((2, &20)), Some((11, &110))));
+    }
+
+    #[test]
+    fn neighbours_skip_the_given_key() {
+        let map = map_of(&[2, 5, 8]);
+        assert_eq!(map.predecessor(5), Some((2, &20)));
+        assert_eq!(map.predecessor(6), Some((5, &50)));
+        assert_eq!(map.predecessor(2), None);
+        assert_eq!(map.successor(5), Some((8, &80)));
+        assert_eq!(map.successor(0), Some((2, &20)));
+        assert_eq!(map.successor(8), None);
+        assert_eq!(map.successor(usize::MAX), None);
+        let empty = OrderedIntMap::<usize>::default();
+        assert_eq!((empty.first(), empty.last(), empty.predecessor(3), empty.successor(3)), (None, None, None, None));
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{IntMap, RefIntMap};
+
+/// The number of values in each page of a [PagedIntMap], unless another is chosen
+pub const DEFAULT_PAGE_SIZE: usize = 256;
+
+struct Page<V> {
+    slots: Box<[Option<V>]>,
+    len: usize,
+}
+
+/**
+A dense [IntMap] split into fixed-size pages, each of which is only allocated once a value is stored in it.
+
+Growing the map never moves values which are already stored, so it never pauses to copy everything it holds.
+Pages are found through an ordered directory holding only the pages which are allocated,
+so a large key costs a single page, however many empty pages lie beneath it. Pages which become empty are freed.
+ */
+pub struct PagedIntMap<V, const PAGE_SIZE: usize = DEFAULT_PAGE_SIZE> {
+    pages: BTreeMap<usize, Page<V>>,
+    counter: usize,
+}
+
+impl <V, const PAGE_SIZE: usize> PagedIntMap<V, PAGE_SIZE> {
+    const VALID_PAGE_SIZE: () = assert!(PAGE_SIZE > 0, "Ids: a PagedIntMap must have a non-zero page size");
+
+    fn set(&mut self, k: usize, elem: Option<V>) {
+        let (page_index, slot) = (k / PAGE_SIZE, k % PAGE_SIZE);
+        match elem {
+            Some(v) => {
+                let page = self.pages.entry(page_index).or_insert_with(|| Page {
+                    slots: (0..PAGE_SIZE).map(|_| None).collect(),
+                    len: 0,
+                });
+                if page.slots[slot].is_none()
+                    { page.len += 1; }
+                page.slots[slot] = Some(v);
+            },
+            None => {
+                let page = match self.pages.get_mut(&page_index) {
+                    Some(page) => page,
+                    None => return,
+                };
+                if page.slots[slot].take().is_some()
+                    { page.len -= 1; }
+                // Free pages once nothing is left in them
+                if page.len == 0
+                    { self.pages.remove(&page_index); }
+            },
+        }
+    }
+
+    /// Get a reference to the value stored under a key, without cloning it
+    pub fn get_ref(&self, k: usize) -> Option<&V> {
+        match self.pages.get(&(k / PAGE_SIZE)) {
+            Some(page) => page.slots[k % PAGE_SIZE].as_ref(),
+            None => None,
+        }
+    }
+
+    /// Iterate over every key and value in this map, in ascending order of key
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &V)> {
+        self.pages.iter()
+            .flat_map(|(n,page)| page.slots.iter().enumerate()
+                .filter_map(move |(slot,v)| v.as_ref().map(|v| (*n*PAGE_SIZE + slot, v))))
+    }
+}
+
+impl <V: Clone, const PAGE_SIZE: usize> IntMap<V> for PagedIntMap<V, PAGE_SIZE> {
+    fn add(&mut self, v: V) -> usize {
+        let id = self.counter;
+        self.set(id, Some(v));
+        self.counter += 1;
+        return id;
+    }
+
+    fn reserve(&mut self) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        return id;
+    }
+
+    fn put(&mut self, k: usize, elem: Option<V>) {
+        if let Some(v) = elem {
+            self.set(k, Some(v));
+            if k >= self.counter
+                { self.counter = k+1 };
+        } else { self.set(k, None); }
+    }
+
+    fn get(&self, k: usize) -> Option<V> { self.get_ref(k).cloned() }
+
+    fn keys(&self) -> Vec<usize> { self.iter().map(|(k,_)| k).collect() }
+
+    fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized {
+        let mut flattened = Self::default();
+        let mut mapping = HashMap::new();
+        for (k,v) in self.iter()
+            { mapping.insert(k, flattened.add(v.clone())); }
+        Ok((flattened, mapping))
+    }
+}
+
+impl <V, const PAGE_SIZE: usize> Default for PagedIntMap<V, PAGE_SIZE> {
+    fn default() -> Self {
+        let () = Self::VALID_PAGE_SIZE;
+        Self { pages: BTreeMap::new(), counter: 0 }
+    }
+}
+
+impl <V: Clone, const PAGE_SIZE: usize> RefIntMap<V> for PagedIntMap<V, PAGE_SIZE> {
+    fn get_ref(&self, k: usize) -> Option<&V> { PagedIntMap::get_ref(self, k) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PagedIntMap;
+    use crate::intmaps::IntMap;
+
+    #[test]
+    fn values_are_found_across_pages_in_ascending_order() {
+        let mut map = PagedIntMap::<u32, 4>::default();
+        for k in [9, 0, 3, 4, 17]
+            { map.put(k, Some(k as u32 * 10)); }
+        assert_eq!(map.keys(), vec![0, 3, 4, 9, 17]);
+        assert_eq!((map.get(3), map.get(4), map.get(5), map.get(17)), (Some(30), Some(40), None, Some(170)));
+        assert_eq!(map.add(1), 18);
+    }
+
+    #[test]
+    fn huge_keys_only_allocate_their_own_page() {
+        let mut map = PagedIntMap::<u8>::default();
+        map.put(1 << 40, Some(1));
+        map.put(usize::MAX - 1, Some(2));
+        assert_eq!(map.pages.len(), 2);
+        assert_eq!(map.keys(), vec![1 << 40, usize::MAX - 1]);
+        assert_eq!(map.get(1 << 40), Some(1));
+    }
+
+    #[test]
+    fn empty_pages_are_freed() {
+        let mut map = PagedIntMap::<u32, 4>::default();
+        map.put(5, Some(5));
+        map.put(6, Some(6));
+        map.rmv(5);
+        assert_eq!(map.pages.len(), 1);
+        map.rmv(6);
+        map.rmv(1000);
+        assert!(map.pages.is_empty());
+        assert_eq!(map.keys(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn flattening_packs_keys_from_zero() {
+        let mut map = PagedIntMap::<u32, 4>::default();
+        for k in [2, 11, 1 << 30]
+            { map.put(k, Some(k as u32)); }
+        let (flattened, mapping) = map.get_flattening().unwrap();
+        assert_eq!(flattened.keys(), vec![0, 1, 2]);
+        assert_eq!((mapping[&2], mapping[&11], mapping[&(1 << 30)]), (0, 1, 2));
+        assert_eq!(flattened.get(2), Some(1 << 30));
+    }
+}
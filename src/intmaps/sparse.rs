@@ -1,22 +1,25 @@
-use std::{hash::Hash, collections::HashMap};
+use std::collections::HashMap;
 
-use super::IntMap;
+use super::{IntMap, RefIntMap};
 
-pub struct SparseIntMap<K: Hash> {
-    inner: HashMap<usize,K>,
+pub struct SparseIntMap<V> {
+    inner: HashMap<usize,V>,
     counter: usize,
 }
 
-impl <V: Hash> SparseIntMap<V> {
+impl <V> SparseIntMap<V> {
     fn set(&mut self, k: usize, elem: Option<V>) {
         match elem {
             Some(v) => { self.inner.insert(k,v); },
             None => { self.inner.remove(&k); },
         }
     }
+
+    pub(super) fn contains_key(&self, k: usize) -> bool { self.inner.contains_key(&k) }
+    pub(super) fn into_entries(self) -> impl Iterator<Item = (usize,V)> { self.inner.into_iter() }
 }
 
-impl <V: Hash + Clone> IntMap<V> for SparseIntMap<V> {
+impl <V: Clone> IntMap<V> for SparseIntMap<V> {
     fn add(&mut self, v: V) -> usize {
         let id = self.counter;
         self.set(id, Some(v));
@@ -24,6 +27,12 @@ impl <V: Hash + Clone> IntMap<V> for SparseIntMap<V> {
         return id;
     }
 
+    fn reserve(&mut self) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        return id;
+    }
+
     fn put(&mut self, k: usize, elem: Option<V>) {
         if let Some(v) = elem {
             self.set(k,Some(v));
@@ -39,6 +48,12 @@ impl <V: Hash + Clone> IntMap<V> for SparseIntMap<V> {
         }
     }
 
+    fn keys(&self) -> Vec<usize> {
+        let mut keys: Vec<usize> = self.inner.keys().copied().collect();
+        keys.sort();
+        return keys;
+    }
+
     fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized {
         // Initialise mapping and flattened vector
         let mut flattened = HashMap::new();
@@ -57,8 +72,12 @@ impl <V: Hash + Clone> IntMap<V> for SparseIntMap<V> {
     }
 }
 
-impl <K: Hash> Default for SparseIntMap<K> {
+impl <V> Default for SparseIntMap<V> {
     fn default() -> Self {
         Self { inner: HashMap::default(), counter: 0 }
     }
 }
+
+impl <V: Clone> RefIntMap<V> for SparseIntMap<V> {
+    fn get_ref(&self, k: usize) -> Option<&V> { self.inner.get(&k) }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use super::{IntMap, PagedIntMap, RefIntMap};
+
+/**
+An [IntMap] storing its values packed together, with a sparse array mapping each key to its value's position.
+
+The sparse array is a [PagedIntMap], so a large key only costs the page which holds it, rather than an array reaching up to it.
+
+Insertion and removal are constant time, and iteration only visits values which are present.
+Removal moves the last value into the removed one's place, so the order of [SparseSetIntMap::iter] changes as values are removed.
+ */
+pub struct SparseSetIntMap<V> {
+    sparse: PagedIntMap<usize>,
+    dense_keys: Vec<usize>,
+    values: Vec<V>,
+    counter: usize,
+}
+
+impl <V> SparseSetIntMap<V> {
+    fn set(&mut self, k: usize, elem: Option<V>) {
+        let position = self.sparse.get(k);
+        match (elem, position) {
+            (Some(v), Some(position)) => self.values[position] = v,
+            (Some(v), None) => {
+                self.sparse.put(k, Some(self.values.len()));
+                self.dense_keys.push(k);
+                self.values.push(v);
+            },
+            (None, Some(position)) => {
+                // Move the last value into the removed value's place
+                self.values.swap_remove(position);
+                self.dense_keys.swap_remove(position);
+                if let Some(moved) = self.dense_keys.get(position)
+                    { self.sparse.put(*moved, Some(position)); }
+                self.sparse.rmv(k);
+            },
+            (None, None) => {},
+        }
+    }
+
+    /// Iterate over every key and value in this map, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &V)> {
+        self.dense_keys.iter().copied().zip(self.values.iter())
+    }
+
+    /// Get every value in this map as a contiguous slice, in the same order as [SparseSetIntMap::iter]
+    pub fn values(&self) -> &[V] { &self.values }
+
+    pub fn len(&self) -> usize { self.values.len() }
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+}
+
+impl <V: Clone> IntMap<V> for SparseSetIntMap<V> {
+    fn add(&mut self, v: V) -> usize {
+        let id = self.counter;
+        self.set(id, Some(v));
+        self.counter += 1;
+        return id;
+    }
+
+    fn reserve(&mut self) -> usize {
+        let id = self.counter;
+        self.counter += 1;
+        return id;
+    }
+
+    fn put(&mut self, k: usize, elem: Option<V>) {
+        if let Some(v) = elem {
+            self.set(k, Some(v));
+            if k >= self.counter
+                { self.counter = k+1 };
+        } else { self.set(k, None); }
+    }
+
+    fn get(&self, k: usize) -> Option<V> {
+        match self.sparse.get(k) {
+            Some(position) => Some(self.values[position].clone()),
+            None => None,
+        }
+    }
+
+    fn keys(&self) -> Vec<usize> { self.sparse.keys() }
+
+    fn get_flattening(&self) -> Result<(Self,HashMap<usize,usize>),()> where Self: Sized {
+        // Renumber keys in ascending order, so that elements keep their relative order
+        let mut flattened = Self::default();
+        let mut mapping = HashMap::new();
+        for k in self.keys() {
+            mapping.insert(k, flattened.len());
+            flattened.add(self.values[self.sparse.get(k).expect("Ids: a key of a sparse set had no value")].clone());
+        }
+        Ok((flattened, mapping))
+    }
+}
+
+impl <V> Default for SparseSetIntMap<V> {
+    fn default() -> Self {
+        Self { sparse: PagedIntMap::default(), dense_keys: Vec::new(), values: Vec::new(), counter: 0 }
+    }
+}
+
+impl <V: Clone> RefIntMap<V> for SparseSetIntMap<V> {
+    fn get_ref(&self, k: usize) -> Option<&V> {
+        match self.sparse.get(k) {
+            Some(position) => Some(&self.values[position]),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseSetIntMap;
+    use crate::intmaps::IntMap;
+
+    fn map_of(values: &[&'static str]) -> SparseSetIntMap<&'static str> {
+        let mut map = SparseSetIntMap::default();
+        for value in values
+            { map.add(*value); }
+        return map;
+    }
+
+    #[test]
+    fn removal_moves_the_last_value_into_the_gap() {
+        let mut map = map_of(&["a", "b", "c", "d"]);
+        map.rmv(1);
+        assert_eq!(map.values(), &["a", "d", "c"]);
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![(0, &"a"), (3, &"d"), (2, &"c")]);
+        assert_eq!((map.get(0), map.get(1), map.get(2), map.get(3)), (Some("a"), None, Some("c"), Some("d")));
+        assert_eq!(map.keys(), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn removing_the_last_or_a_missing_value_leaves_the_rest() {
+        let mut map = map_of(&["a", "b"]);
+        map.rmv(1);
+        map.rmv(1);
+        map.rmv(50);
+        assert_eq!(map.values(), &["a"]);
+        map.rmv(0);
+        assert!(map.is_empty());
+        assert_eq!(map.get(0), None);
+        // Removed keys are not handed out again
+        assert_eq!(map.add("c"), 2);
+    }
+
+    #[test]
+    fn put_replaces_in_place_and_extends_the_counter() {
+        let mut map = map_of(&["a", "b"]);
+        map.put(0, Some("z"));
+        map.put(10, Some("y"));
+        assert_eq!(map.values(), &["z", "b", "y"]);
+        assert_eq!(map.add("x"), 11);
+        assert_eq!(map.reserve(), 12);
+        assert_eq!(map.add("w"), 13);
+    }
+
+    #[test]
+    fn huge_keys_do_not_allocate_up_to_them() {
+        let mut map = map_of(&["a"]);
+        map.put(1 << 40, Some("far"));
+        assert_eq!(map.get(1 << 40), Some("far"));
+        assert_eq!(map.keys(), vec![0, 1 << 40]);
+        map.rmv(0);
+        assert_eq!(map.values(), &["far"]);
+        assert_eq!(map.get(1 << 40), Some("far"));
+        assert_eq!(map.add("b"), (1 << 40) + 1);
+    }
+
+    #[test]
+    fn flattening_keeps_ascending_order() {
+        let mut map = map_of(&["a", "b", "c", "d", "e"]);
+        map.rmv(0);
+        map.rmv(2);
+        let (flattened, mapping) = map.get_flattening().unwrap();
+        assert_eq!(flattened.keys(), vec![0, 1, 2]);
+        assert_eq!((flattened.get(0), flattened.get(1), flattened.get(2)), (Some("b"), Some("d"), Some("e")));
+        assert_eq!((mapping[&1], mapping[&3], mapping[&4]), (0, 1, 2));
+        assert_eq!(mapping.len(), 3);
+    }
+}
@@ -1,8 +1,12 @@
+#![allow(clippy::needless_return, clippy::result_unit_err, clippy::question_mark, clippy::manual_map)]
+
 mod base;
 mod implementations;
 mod utils;
 mod intmaps;
+mod errors;
 
 pub use base::*;
 pub use implementations::*;
 pub use utils::*;
+pub use intmaps::*;
@@ -0,0 +1,261 @@
+use std::any::Any;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use crate::intmaps::{DenseIntMap, RefIntMap, SparseIntMap};
+use crate::IdImpl;
+use crate::errors::{CONVERT_FROM_USIZE_ERROR, CONVERT_TO_USIZE_ERROR};
+use crate::trackers::next_tracker_tag;
+
+/**
+A handle to one of the columns owned by an [EntityAllocator], which remembers the type of the column.
+
+A handle can only be used with the allocator which created it.
+ */
+pub struct ColumnHandle<C> {
+    allocator: u64,
+    position: usize,
+    c: PhantomData<fn() -> C>,
+}
+
+impl <C> Clone for ColumnHandle<C> {
+    fn clone(&self) -> Self { *self }
+}
+impl <C> Copy for ColumnHandle<C> {}
+
+/// A column whose type has been erased, so that columns of different components can be owned together
+trait OwnedColumn<E: IdImpl> {
+    fn remove(&mut self, entity: E);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl <E: IdImpl, C: EntityColumn<E> + 'static> OwnedColumn<E> for C {
+    fn remove(&mut self, entity: E) { EntityColumn::remove(self, entity) }
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+/**
+Allocates entity ids from an [IdImpl], reusing the ids of entities which have been freed.
+
+The allocator owns the columns of components which are kept for its entities, so that freeing an entity removes every one of its components,
+and a later entity given the same id does not inherit them. Since ids are reused, an id should not be kept after its entity has been freed.
+ */
+pub struct EntityAllocator<E: IdImpl> {
+    next: Option<E>,
+    free: Vec<E>,
+    alive: HashSet<E>,
+    tag: u64,
+    columns: Vec<Box<dyn OwnedColumn<E> + Send + Sync>>,
+}
+
+impl <E: IdImpl + 'static> EntityAllocator<E> {
+    /// Allocate a new entity, returning an [Err] if every id of this type is already in use
+    pub fn allocate(&mut self) -> Result<E,()> {
+        let entity = match self.free.pop() {
+            Some(entity) => entity,
+            None => match self.next {
+                Some(entity) => { self.next = entity.next().ok(); entity },
+                None => return Err(()),
+            },
+        }; self.alive.insert(entity);
+        return Ok(entity);
+    }
+
+    /// Free an entity so that its id can be reused, removing its components from every column of the allocator. Returns whether the entity was alive.
+    pub fn free(&mut self, entity: E) -> bool {
+        let was_alive = self.alive.remove(&entity);
+        if was_alive {
+            for column in self.columns.iter_mut()
+                { column.remove(entity); }
+            self.free.push(entity);
+        }; return was_alive;
+    }
+
+    pub fn is_alive(&self, entity: E) -> bool { self.alive.contains(&entity) }
+
+    /// Get every living entity, in no particular order
+    pub fn entities(&self) -> Vec<E> { self.alive.iter().copied().collect() }
+
+    /// Give a column to the allocator, so that its components are removed whenever their entities are freed
+    pub fn add_column<C: EntityColumn<E> + Send + Sync + 'static>(&mut self, column: C) -> ColumnHandle<C> {
+        self.columns.push(Box::new(column));
+        return ColumnHandle { allocator: self.tag, position: self.columns.len() - 1, c: PhantomData };
+    }
+
+    /// Get the column which a handle refers to, or an [Err] if the handle was created by another allocator
+    pub fn column<C: 'static>(&self, column: ColumnHandle<C>) -> Result<&C,()> {
+        if column.allocator != self.tag
+            { return Err(()) }
+        match self.columns.get(column.position).and_then(|found| found.as_any().downcast_ref::<C>()) {
+            Some(found) => Ok(found),
+            None => Err(()),
+        }
+    }
+
+    /// Get the column which a handle refers to mutably, or an [Err] if the handle was created by another allocator
+    pub fn column_mut<C: 'static>(&mut self, column: ColumnHandle<C>) -> Result<&mut C,()> {
+        if column.allocator != self.tag
+            { return Err(()) }
+        match self.columns.get_mut(column.position).and_then(|found| found.as_any_mut().downcast_mut::<C>()) {
+            Some(found) => Ok(found),
+            None => Err(()),
+        }
+    }
+}
+
+impl <E: IdImpl> Default for EntityAllocator<E> {
+    fn default() -> Self {
+        Self { next: Some(E::first()), free: Vec::new(), alive: HashSet::new(), tag: next_tracker_tag(), columns: Vec::new() }
+    }
+}
+
+/**
+The operations of a [ComponentColumn] which do not depend on its component type, so that columns of different components can be handled together.
+ */
+pub trait EntityColumn<E: IdImpl> {
+    /// Remove the component of an entity, if it has one
+    fn remove(&mut self, entity: E);
+    /// Get every entity which has this component, in ascending order
+    fn entities(&self) -> Vec<E>;
+}
+
+/**
+A column storing one kind of component for some entities, keyed by entity id.
+ */
+pub trait ComponentColumn<E: IdImpl, V>: EntityColumn<E> {
+    /// Set the component of an entity, replacing any it already had
+    fn insert(&mut self, entity: E, component: V);
+    fn get(&self, entity: E) -> Option<V>;
+    /// Get a reference to the component of an entity, without cloning it
+    fn get_ref(&self, entity: E) -> Option<&V>;
+    fn contains(&self, entity: E) -> bool { self.get(entity).is_some() }
+}
+
+/**
+A [ComponentColumn] backed by any [RefIntMap].
+
+[DenseComponents] suits components which most entities have, and [SparseComponents] suits those which few entities have.
+ */
+pub struct Components<E: IdImpl, V, M: RefIntMap<V>> {
+    map: M,
+    p: PhantomData<(E,V)>,
+}
+
+/// A [ComponentColumn] for components which most entities have
+pub type DenseComponents<E, V> = Components<E, V, DenseIntMap<V>>;
+/// A [ComponentColumn] for components which few entities have
+pub type SparseComponents<E, V> = Components<E, V, SparseIntMap<V>>;
+
+impl <E: IdImpl, V, M: RefIntMap<V>> EntityColumn<E> for Components<E,V,M> {
+    fn remove(&mut self, entity: E) {
+        self.map.rmv(entity.try_into().expect(CONVERT_TO_USIZE_ERROR))
+    }
+    fn entities(&self) -> Vec<E> {
+        self.map.keys().into_iter()
+            .map(|k| E::try_from(k).expect(CONVERT_FROM_USIZE_ERROR))
+            .collect()
+    }
+}
+
+impl <E: IdImpl, V, M: RefIntMap<V>> ComponentColumn<E,V> for Components<E,V,M> {
+    fn insert(&mut self, entity: E, component: V) {
+        self.map.put(entity.try_into().expect(CONVERT_TO_USIZE_ERROR), Some(component))
+    }
+    fn get(&self, entity: E) -> Option<V> {
+        self.map.get(entity.try_into().expect(CONVERT_TO_USIZE_ERROR))
+    }
+    fn get_ref(&self, entity: E) -> Option<&V> {
+        self.map.get_ref(entity.try_into().expect(CONVERT_TO_USIZE_ERROR))
+    }
+}
+
+impl <E: IdImpl, V, M: RefIntMap<V> + Default> Default for Components<E,V,M> {
+    fn default() -> Self {
+        Self { map: M::default(), p: PhantomData }
+    }
+}
+
+/// Get every entity which has a component in both columns, along with references to both of its components, in ascending order of entity
+pub fn join2<'c, E, A, B, CA, CB>(a: &'c CA, b: &'c CB) -> impl Iterator<Item = (E,&'c A,&'c B)> + 'c
+where E: IdImpl + 'c, A: 'c, B: 'c, CA: ComponentColumn<E,A>, CB: ComponentColumn<E,B> {
+    // Walk the shorter column, and look up the longer one
+    let (a_entities, b_entities) = (a.entities(), b.entities());
+    let entities = if a_entities.len() <= b_entities.len() { a_entities } else { b_entities };
+    entities.into_iter()
+        .filter_map(move |entity| match (a.get_ref(entity), b.get_ref(entity)) {
+            (Some(a), Some(b)) => Some((entity, a, b)),
+            _ => None,
+        })
+}
+
+/// Get every entity which has a component in all three columns, along with references to all of its components, in ascending order of entity
+pub fn join3<'c, E, A, B, C, CA, CB, CC>(a: &'c CA, b: &'c CB, c: &'c CC) -> impl Iterator<Item = (E,&'c A,&'c B,&'c C)> + 'c
+where E: IdImpl + 'c, A: 'c, B: 'c, C: 'c, CA: ComponentColumn<E,A>, CB: ComponentColumn<E,B>, CC: ComponentColumn<E,C> {
+    let entities = [a.entities(), b.entities(), c.entities()].into_iter()
+        .min_by_key(|entities| entities.len())
+        .unwrap_or_default();
+    entities.into_iter()
+        .filter_map(move |entity| match (a.get_ref(entity), b.get_ref(entity), c.get_ref(entity)) {
+            (Some(a), Some(b), Some(c)) => Some((entity, a, b, c)),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{join2, join3, ComponentColumn, DenseComponents, EntityAllocator, SparseComponents};
+    use crate::Id32;
+
+    #[test]
+    fn reused_entities_do_not_inherit_components() {
+        let mut entities = EntityAllocator::<Id32>::default();
+        let positions = entities.add_column(DenseComponents::<Id32,u32>::default());
+        let names = entities.add_column(SparseComponents::<Id32,&str>::default());
+        let entity = entities.allocate().unwrap();
+        entities.column_mut(positions).unwrap().insert(entity, 7);
+        entities.column_mut(names).unwrap().insert(entity, "first");
+        assert!(entities.free(entity));
+        assert!(!entities.free(entity));
+        let reused = entities.allocate().unwrap();
+        assert_eq!(reused, entity);
+        assert_eq!(entities.column(positions).unwrap().get(reused), None);
+        assert_eq!(entities.column(names).unwrap().get(reused), None);
+    }
+
+    #[test]
+    fn columns_belong_to_their_allocator() {
+        let mut entities = EntityAllocator::<Id32>::default();
+        let mut others = EntityAllocator::<Id32>::default();
+        entities.add_column(DenseComponents::<Id32,u32>::default());
+        let foreign = others.add_column(DenseComponents::<Id32,u32>::default());
+        assert!(entities.column(foreign).is_err());
+        assert!(entities.column_mut(foreign).is_err());
+        assert!(others.column(foreign).is_ok());
+    }
+
+    #[test]
+    fn joins_yield_references_in_ascending_order() {
+        let mut entities = EntityAllocator::<Id32>::default();
+        let positions = entities.add_column(DenseComponents::<Id32,u32>::default());
+        let names = entities.add_column(SparseComponents::<Id32,String>::default());
+        let flags = entities.add_column(SparseComponents::<Id32,bool>::default());
+        let all: Vec<Id32> = (0..5).map(|_| entities.allocate().unwrap()).collect();
+        for (n, entity) in all.iter().enumerate() {
+            entities.column_mut(positions).unwrap().insert(*entity, n as u32);
+            if n % 2 == 0
+                { entities.column_mut(names).unwrap().insert(*entity, format!("e{}", n)); }
+        }
+        entities.column_mut(flags).unwrap().insert(all[4], true);
+        entities.column_mut(flags).unwrap().insert(all[1], false);
+
+        let (positions, names, flags) = (entities.column(positions).unwrap(), entities.column(names).unwrap(), entities.column(flags).unwrap());
+        let joined: Vec<(Id32,&u32,&String)> = join2(positions, names).collect();
+        assert_eq!(joined, vec![(all[0], &0, &"e0".to_string()), (all[2], &2, &"e2".to_string()), (all[4], &4, &"e4".to_string())]);
+        // The components are lent from the columns rather than cloned
+        assert!(std::ptr::eq(joined[0].2, names.get_ref(all[0]).unwrap()));
+        let joined: Vec<(Id32,&u32,&String,&bool)> = join3(positions, names, flags).collect();
+        assert_eq!(joined, vec![(all[4], &4, &"e4".to_string(), &true)]);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use crate::intmaps::{IntMap, SparseIntMap};
+use crate::{CompositeId, Identifier, IdentifierPath};
+use crate::errors::{CONVERT_FROM_USIZE_ERROR, CONVERT_TO_USIZE_ERROR};
+
+use super::UpdatableIdStore;
+
+/**
+A store of values keyed by [CompositeId]s, which allocates child ids separately within each parent.
+
+This allows children to be looked up by their parent, such as every paragraph within a document.
+ */
+pub struct CompositeIdStore<P: IdentifierPath, C: Identifier, V: Clone> {
+    children: HashMap<P, SparseIntMap<V>>,
+    c: std::marker::PhantomData<C>,
+}
+
+impl <P: IdentifierPath, C: Identifier, V: Clone> CompositeIdStore<P,C,V> {
+    /// Insert a value under a parent, allocating the next child id within that parent
+    pub fn insert(&mut self, parent: P, value: V) -> CompositeId<P,C> {
+        let child = self.children.entry(parent).or_default().add(value);
+        CompositeId::new(parent, C::try_from(child).expect(CONVERT_FROM_USIZE_ERROR))
+    }
+
+    /// Set or clear the value at a particular id. Later allocations within the same parent will not reuse it.
+    pub fn put(&mut self, id: CompositeId<P,C>, value: Option<V>) {
+        let child = id.child().try_into().expect(CONVERT_TO_USIZE_ERROR);
+        match value {
+            Some(value) => self.children.entry(id.parent()).or_default().put(child, Some(value)),
+            None => if let Some(children) = self.children.get_mut(&id.parent()) { children.rmv(child) },
+        }
+    }
+
+    pub fn get(&self, id: CompositeId<P,C>) -> Option<V> {
+        match self.children.get(&id.parent()) {
+            Some(children) => children.get(id.child().try_into().expect(CONVERT_TO_USIZE_ERROR)),
+            None => None,
+        }
+    }
+
+    pub fn remove(&mut self, id: CompositeId<P,C>) { self.put(id, None) }
+
+    /// Get the ids of every child within a parent, in ascending order
+    pub fn children(&self, parent: P) -> Vec<CompositeId<P,C>> {
+        match self.children.get(&parent) {
+            Some(children) => children.keys().into_iter()
+                .map(|child| CompositeId::new(parent, C::try_from(child).expect(CONVERT_FROM_USIZE_ERROR)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Get every child within a parent along with its value, in ascending order of id
+    pub fn get_by_prefix(&self, parent: P) -> Vec<(CompositeId<P,C>,V)> {
+        self.children(parent).into_iter()
+            .filter_map(|id| self.get(id).map(|value| (id, value)))
+            .collect()
+    }
+
+    /// Remove a parent along with all of its children. Child ids within it will be allocated from the start again.
+    pub fn remove_prefix(&mut self, parent: P) { self.children.remove(&parent); }
+}
+
+impl <P: IdentifierPath, C: Identifier, V: Clone> Default for CompositeIdStore<P,C,V> {
+    fn default() -> Self {
+        Self { children: HashMap::new(), c: std::marker::PhantomData }
+    }
+}
+
+impl <P: Identifier, C: Identifier, V: Clone> UpdatableIdStore<P> for CompositeIdStore<P,C,V> {
+    /// Move the children of every parent to that parent's new id. Child ids are unaffected.
+    fn update_ids(&mut self, mapping: &HashMap<P,P>) {
+        let mut new_children = HashMap::new();
+        for (parent, children) in self.children.drain() {
+            let new_parent = match mapping.get(&parent) {
+                Some(val) => val,
+                None => panic!("Attempted to update an UpdatableIdStore without supplying a complete list of updates"),
+            }; new_children.insert(*new_parent, children);
+        }
+        self.children = new_children;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::CompositeIdStore;
+    use crate::{CompositeId, Id32, TypedId};
+    use crate::UpdatableIdStore;
+
+    struct Documents;
+    struct Paragraphs;
+    type DocumentId = TypedId<Documents, Id32>;
+    type ParagraphId = TypedId<Paragraphs, Id32>;
+
+    fn document(n: u32) -> DocumentId { DocumentId::new(Id32(n)) }
+    fn paragraph(n: u32) -> ParagraphId { ParagraphId::new(Id32(n)) }
+
+    #[test]
+    fn children_are_allocated_separately_within_each_parent() {
+        let mut store = CompositeIdStore::<DocumentId,ParagraphId,&str>::default();
+        let a0 = store.insert(document(1), "a0");
+        let b0 = store.insert(document(2), "b0");
+        let a1 = store.insert(document(1), "a1");
+        assert_eq!(a0, CompositeId::first(document(1)));
+        assert_eq!(b0, CompositeId::first(document(2)));
+        assert_eq!(a1, a0.next());
+        assert_eq!(store.get(a1), Some("a1"));
+        assert_eq!(store.get(CompositeId::new(document(2), paragraph(1))), None);
+
+        // Removed children are not reused within their parent
+        store.remove(a1);
+        assert_eq!(store.insert(document(1), "a2"), a1.next());
+    }
+
+    #[test]
+    fn children_are_looked_up_by_their_parent() {
+        let mut store = CompositeIdStore::<DocumentId,ParagraphId,&str>::default();
+        let a0 = store.insert(document(1), "a0");
+        store.insert(document(2), "b0");
+        let a1 = store.insert(document(1), "a1");
+        store.put(CompositeId::new(document(1), paragraph(5)), Some("a5"));
+        assert_eq!(store.get_by_prefix(document(1)), vec![(a0, "a0"), (a1, "a1"), (CompositeId::new(document(1), paragraph(5)), "a5")]);
+        assert_eq!(store.children(document(3)), vec![]);
+
+        store.remove_prefix(document(1));
+        assert_eq!(store.get_by_prefix(document(1)), vec![]);
+        assert_eq!(store.insert(document(1), "new"), a0);
+        assert_eq!(store.children(document(2)), vec![CompositeId::first(document(2))]);
+    }
+
+    #[test]
+    fn updating_parent_ids_keeps_their_children() {
+        let mut store = CompositeIdStore::<DocumentId,ParagraphId,&str>::default();
+        store.insert(document(3), "c0");
+        store.insert(document(7), "g0");
+        let mapping = HashMap::from([(document(3), document(0)), (document(7), document(1))]);
+        store.update_ids(&mapping);
+        assert_eq!(store.get_by_prefix(document(0)), vec![(CompositeId::first(document(0)), "c0")]);
+        assert_eq!(store.get_by_prefix(document(1)), vec![(CompositeId::first(document(1)), "g0")]);
+        assert_eq!(store.children(document(3)), vec![]);
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+use crate::intmaps::{DenseIntMap, IntMap};
+use crate::Identifier;
+use crate::errors::{CONVERT_FROM_USIZE_ERROR, CONVERT_TO_USIZE_ERROR};
+
+/**
+Maps values to compact [Identifier]s and back, giving equal values the same id.
+
+Ids are allocated densely from the first id, and are never reused or invalidated.
+ */
+pub struct Interner<I: Identifier, V: Hash + Eq + Clone> {
+    values: DenseIntMap<V>,
+    ids: HashMap<V,I>,
+}
+
+impl <I: Identifier, V: Hash + Eq + Clone> Interner<I,V> {
+    /// Get the id of a value, giving it a new id if it has not been interned before
+    pub fn intern(&mut self, value: V) -> I {
+        if let Some(id) = self.ids.get(&value)
+            { return *id; }
+        let id = I::try_from(self.values.add(value.clone())).expect(CONVERT_FROM_USIZE_ERROR);
+        self.ids.insert(value, id);
+        return id;
+    }
+
+    /// Get the id of a value, if it has been interned
+    pub fn get_id(&self, value: &V) -> Option<I> { self.ids.get(value).copied() }
+
+    /// Get the value which an id was given to
+    pub fn resolve(&self, id: I) -> Option<V> {
+        self.values.get(id.try_into().expect(CONVERT_TO_USIZE_ERROR))
+    }
+
+    /// Get the number of distinct values which have been interned
+    pub fn len(&self) -> usize { self.ids.len() }
+    pub fn is_empty(&self) -> bool { self.ids.is_empty() }
+}
+
+impl <I: Identifier, V: Hash + Eq + Clone> Default for Interner<I,V> {
+    fn default() -> Self {
+        Self { values: DenseIntMap::default(), ids: HashMap::new() }
+    }
+}
+
+/**
+An [Interner] specialised for strings, which stores the bytes of every interned string contiguously in one buffer.
+
+This avoids an allocation per string, and avoids storing each string twice.
+ */
+pub struct StringInterner<I: Identifier> {
+    bytes: String,
+    spans: DenseIntMap<(usize,usize)>,
+    buckets: HashMap<u64,Vec<I>>,
+    hasher: RandomState,
+    len: usize,
+}
+
+impl <I: Identifier> StringInterner<I> {
+    /// Get the id of a string, giving it a new id if it has not been interned before
+    pub fn intern(&mut self, value: &str) -> I {
+        let hash = self.hasher.hash_one(value);
+        if let Some(id) = self.find(hash, value)
+            { return id; }
+        // Append the string to the buffer, and remember where it is
+        let start = self.bytes.len();
+        self.bytes.push_str(value);
+        let id = I::try_from(self.spans.add((start, self.bytes.len()))).expect(CONVERT_FROM_USIZE_ERROR);
+        self.buckets.entry(hash).or_default().push(id);
+        self.len += 1;
+        return id;
+    }
+
+    /// Get the id of a string, if it has been interned
+    pub fn get_id(&self, value: &str) -> Option<I> {
+        self.find(self.hasher.hash_one(value), value)
+    }
+
+    /// Get the string which an id was given to
+    pub fn resolve(&self, id: I) -> Option<&str> {
+        self.spans.get(id.try_into().expect(CONVERT_TO_USIZE_ERROR))
+            .map(|(start, end)| &self.bytes[start..end])
+    }
+
+    /// Get the number of distinct strings which have been interned
+    pub fn len(&self) -> usize { self.len }
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Find a string among those with the same hash
+    fn find(&self, hash: u64, value: &str) -> Option<I> {
+        match self.buckets.get(&hash) {
+            Some(bucket) => bucket.iter().copied().find(|id| self.resolve(*id) == Some(value)),
+            None => None,
+        }
+    }
+}
+
+impl <I: Identifier> Default for StringInterner<I> {
+    fn default() -> Self {
+        Self {
+            bytes: String::new(),
+            spans: DenseIntMap::default(),
+            buckets: HashMap::new(),
+            hasher: RandomState::new(),
+            len: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interner, StringInterner};
+    use crate::{Id32, Identifier, TypedId};
+
+    struct Symbols;
+    type SymbolId = TypedId<Symbols, Id32>;
+
+    #[test]
+    fn interners_give_equal_values_the_same_id() {
+        let mut interner = Interner::<SymbolId,(u8,u8)>::default();
+        assert!(interner.is_empty());
+        let a = interner.intern((1, 2));
+        let b = interner.intern((2, 1));
+        assert_eq!(a, SymbolId::first());
+        assert_eq!(b, a.next());
+        assert_eq!(interner.intern((1, 2)), a);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get_id(&(2, 1)), Some(b));
+        assert_eq!(interner.get_id(&(3, 3)), None);
+        assert_eq!(interner.resolve(b), Some((2, 1)));
+        assert_eq!(interner.resolve(b.next()), None);
+    }
+
+    #[test]
+    fn string_interners_give_equal_strings_the_same_id() {
+        let mut interner = StringInterner::<SymbolId>::default();
+        let words = ["apple", "", "banana", "apple", "app", ""];
+        let ids: Vec<SymbolId> = words.iter().map(|word| interner.intern(word)).collect();
+        assert_eq!(ids[3], ids[0]);
+        assert_eq!(ids[5], ids[1]);
+        // A prefix of an earlier string is still a string of its own
+        assert_ne!(ids[4], ids[0]);
+        assert_eq!(interner.len(), 4);
+        for (word, id) in words.iter().zip(&ids) {
+            assert_eq!(interner.resolve(*id), Some(*word));
+            assert_eq!(interner.get_id(word), Some(*id));
+        }
+        assert_eq!(interner.get_id("cherry"), None);
+        assert_eq!(interner.resolve(SymbolId::new(Id32(4))), None);
+    }
+}
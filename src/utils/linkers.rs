@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use bimap::BiHashMap;
 
-use crate::Identifier;
+use crate::{Identifier, KeyBytes, IdDecodeError};
 
 use super::UpdatableIdStore;
 
@@ -32,12 +32,45 @@ impl <T1: Identifier, T2: Identifier> IdLinker<T1,T2> {
     pub fn delete_by_right(&mut self, right: &T2)
         { self.map.remove_by_right(right); }
 
-    pub fn left_updater(&mut self) -> LeftLinkerUpdater<T1,T2>
+    /// Iterate over every linked pair of ids, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&T1, &T2)>
+        { self.map.iter() }
+
+    pub fn left_updater(&mut self) -> LeftLinkerUpdater<'_,T1,T2>
         { LeftLinkerUpdater { linker: self } }
-    pub fn right_updater(&mut self) -> RightLinkerUpdater<T1,T2>
+    pub fn right_updater(&mut self) -> RightLinkerUpdater<'_,T1,T2>
         { RightLinkerUpdater { linker: self } }
 }
 
+impl <T1: Identifier + KeyBytes, T2: Identifier + KeyBytes> IdLinker<T1,T2> {
+    /**
+    Get the key bytes of every linked pair of ids, sorted in key order.
+
+    Each key is the left id followed by the right id, so they can be stored in an ordered key-value store and scanned by left id.
+     */
+    pub fn to_key_bytes(&self) -> Vec<Vec<u8>> {
+        let mut keys: Vec<Vec<u8>> = self.map.iter()
+            .map(|(left,right)| (*left,*right).to_key_bytes())
+            .collect();
+        keys.sort();
+        return keys;
+    }
+
+    /// Link a pair of ids read from key bytes, as produced by [IdLinker::to_key_bytes]
+    pub fn insert_key_bytes(&mut self, key: &[u8]) -> Result<(),IdDecodeError> {
+        match <(T1,T2)>::from_key_bytes(key) {
+            Ok((left, right)) => { self.insert(left, right); Ok(()) },
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl <T1: Identifier, T2: Identifier> Default for IdLinker<T1,T2> {
+    fn default() -> Self {
+        Self { map: BiHashMap::new() }
+    }
+}
+
 /// A struct which provides an interface for updating IdLinkers by their left id type
 pub struct LeftLinkerUpdater<'a, T1: Identifier, T2: Identifier> {
     linker: &'a mut IdLinker<T1,T2>
@@ -47,7 +80,7 @@ impl <T1: Identifier, T2: Identifier> UpdatableIdStore<T1> for LeftLinkerUpdater
     fn update_ids(&mut self, mapping: &HashMap<T1,T1>) {
         let mut new_map = BiHashMap::<T1,T2>::new();
         for (left,right) in &self.linker.map {
-            let new_left = match mapping.get(&left) {
+            let new_left = match mapping.get(left) {
                 Some(val) => val,
                 None => panic!("Attempted to update an UpdatableIdStore without supplying a complete list of updates"),
             }; new_map.insert(*new_left, *right);
@@ -65,7 +98,7 @@ impl <T1: Identifier, T2: Identifier> UpdatableIdStore<T2> for RightLinkerUpdate
     fn update_ids(&mut self, mapping: &HashMap<T2,T2>) {
         let mut new_map = BiHashMap::<T1,T2>::new();
         for (left,right) in &self.linker.map {
-            let new_right = match mapping.get(&right) {
+            let new_right = match mapping.get(right) {
                 Some(val) => val,
                 None => panic!("Attempted to update an UpdatableIdStore without supplying a complete list of updates"),
             }; new_map.insert(*left, *new_right);
@@ -1,5 +1,8 @@
 pub mod trackers;
 pub mod linkers;
+pub mod composites;
+pub mod interners;
+pub mod components;
 
 use std::collections::HashMap;
 
@@ -0,0 +1,92 @@
+use std::{sync::{Mutex, Arc}, collections::HashMap};
+
+use crate::{Identifier, IdentifiedBy, intmaps::AdaptiveIntMap, UpdatableIdStore};
+
+use super::{inner::IdTrackerInner, IdTracker, ReservingIdTracker};
+
+/**
+An [IdTracker] which stores its elements densely while most of its ids are in use, and sparsely once many have been removed.
+
+This suits trackers whose elements are removed in bursts, and which are not flattened often enough to stay dense.
+ */
+pub struct AdaptiveIdTracker<I: Identifier, T: IdentifiedBy<I>> {
+    inner: IdTrackerInner<I,T,AdaptiveIntMap<Arc<Mutex<T>>>>
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> AdaptiveIdTracker<I,T> {
+    /**
+    Create an empty tracker which becomes sparse when its occupancy falls below `sparse_below`, and dense when it rises above `dense_above`.
+
+    Returns an [Err] unless `0 <= sparse_below <= dense_above <= 1`, as with [AdaptiveIntMap::with_thresholds].
+     */
+    pub fn with_thresholds(sparse_below: f64, dense_above: f64) -> Result<Self,()> {
+        match AdaptiveIntMap::with_thresholds(sparse_below, dense_above) {
+            Ok(map) => Ok(Self { inner: IdTrackerInner::with_map(map) }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Whether this tracker is currently storing its elements densely
+    pub fn is_dense(&self) -> bool { self.inner.map().is_dense() }
+    pub fn len(&self) -> usize { self.inner.map().len() }
+    pub fn is_empty(&self) -> bool { self.inner.map().is_empty() }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> IdTracker<I,T> for AdaptiveIdTracker<I,T> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.get(id) }
+    fn put(&mut self, element: T) -> Arc<Mutex<T>> { self.inner.put(element) }
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.remove(id) }
+    fn ids(&self) -> Vec<I> { self.inner.ids() }
+    fn flatten(&mut self) -> Result<std::collections::HashMap<I,I>,()> { self.inner.flatten() }
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>
+        { self.inner.flatten_with(stores_to_update) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> ReservingIdTracker<I,T> for AdaptiveIdTracker<I,T> {
+    fn reserve(&mut self) -> I { self.inner.reserve() }
+    fn fill(&mut self, id: I, element: T) -> Result<Arc<Mutex<T>>,()> { self.inner.fill(id, element) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> Default for AdaptiveIdTracker<I,T> {
+    fn default() -> Self {
+        Self { inner: IdTrackerInner::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdaptiveIdTracker;
+    use crate::trackers::IdTracker;
+    use crate::trackers::testing::{Item, ItemId};
+
+    #[test]
+    fn switches_to_sparse_after_removals_and_back_when_flattened() {
+        let mut tracker = AdaptiveIdTracker::default();
+        let ids: Vec<ItemId> = (0..100)
+            .map(|n| tracker.put(Item::new(&n.to_string())).lock().unwrap().id)
+            .collect();
+        for id in &ids[..90]
+            { tracker.remove(*id); }
+        assert!(!tracker.is_dense());
+        assert_eq!(tracker.len(), 10);
+        assert_eq!(tracker.get(ids[95]).unwrap().lock().unwrap().name, "95");
+
+        let mapping = tracker.flatten().unwrap();
+        assert!(tracker.is_dense());
+        assert_eq!(tracker.get(mapping[&ids[95]]).unwrap().lock().unwrap().name, "95");
+        assert_eq!(tracker.ids().len(), 10);
+    }
+
+    #[test]
+    fn thresholds_are_passed_to_the_map() {
+        assert!(AdaptiveIdTracker::<ItemId,Item>::with_thresholds(0.6, 0.3).is_err());
+        // A tracker which never goes sparse stays dense however many elements are removed
+        let mut tracker = AdaptiveIdTracker::with_thresholds(0.0, 0.5).unwrap();
+        let ids: Vec<ItemId> = (0..100)
+            .map(|n| tracker.put(Item::new(&n.to_string())).lock().unwrap().id)
+            .collect();
+        for id in &ids[..99]
+            { tracker.remove(*id); }
+        assert!(tracker.is_dense());
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::{Mutex, Arc};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{IdentifiedBy, Identifier};
+use crate::errors::NEW_MUTEX_POISONED_ERROR;
+
+use super::IdTracker;
+
+#[cfg(debug_assertions)]
+const WRONG_TRACKER_ERROR: &str = "Ids: A TaggedId was used with a tracker other than the one which issued it";
+
+/// An invariant lifetime, which can neither be shortened nor lengthened to match another
+type Brand<'brand> = PhantomData<fn(&'brand ()) -> &'brand ()>;
+
+/**
+An id which is statically known to have been issued by one particular [BrandedTracker].
+
+The `'brand` lifetime is unique to each call of [with_brand], so a [BrandedId] cannot be used with any other tracker,
+even another tracker of the same type.
+ */
+pub struct BrandedId<'brand, I: Identifier> {
+    id: I,
+    brand: Brand<'brand>,
+}
+
+impl <I: Identifier> BrandedId<'_,I> {
+    /// Get the underlying id, discarding the brand
+    pub fn id(self) -> I { self.id }
+}
+
+impl <I: Identifier> Clone for BrandedId<'_,I> {
+    fn clone(&self) -> Self { *self }
+}
+impl <I: Identifier> Copy for BrandedId<'_,I> {}
+impl <I: Identifier> PartialEq for BrandedId<'_,I> {
+    fn eq(&self, other: &Self) -> bool { self.id == other.id }
+}
+impl <I: Identifier> Eq for BrandedId<'_,I> {}
+impl <I: Identifier> Hash for BrandedId<'_,I> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) { self.id.hash(state) }
+}
+impl <I: Identifier> Debug for BrandedId<'_,I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("BrandedId").field(&self.id).finish()
+    }
+}
+
+/**
+A view of a tracker which only accepts ids that it issued itself. It can only be created by [with_brand].
+
+Flattening is not available through this view, since it would invalidate the ids which have already been issued.
+ */
+pub struct BrandedTracker<'a, 'brand, I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> {
+    tracker: &'a mut Tr,
+    p: PhantomData<(I,T)>,
+    brand: Brand<'brand>,
+}
+
+impl <'brand, I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> BrandedTracker<'_,'brand,I,T,Tr> {
+    /**
+    Get an element of this tracker.
+
+    Ids issued by this view are never invalidated, since it cannot remove or flatten.
+    This only returns [None] if an element's [IdentifiedBy] implementation did not report the id it was given.
+     */
+    pub fn get(&self, id: BrandedId<'brand,I>) -> Option<Arc<Mutex<T>>> {
+        self.tracker.get(id.id)
+    }
+
+    /// Insert an element into this tracker, returning its branded id along with the element
+    pub fn put(&mut self, element: T) -> (BrandedId<'brand,I>, Arc<Mutex<T>>) {
+        let element = self.tracker.put(element);
+        let id = element.lock().expect(NEW_MUTEX_POISONED_ERROR).get_id();
+        (BrandedId { id, brand: PhantomData }, element)
+    }
+
+    /// Check whether an unbranded id belongs to this tracker, and brand it if so
+    pub fn brand(&self, id: I) -> Option<BrandedId<'brand,I>> {
+        self.tracker.get(id).map(|_| BrandedId { id, brand: PhantomData })
+    }
+
+    /// Get the branded ids of every element in this tracker, in ascending order
+    pub fn ids(&self) -> Vec<BrandedId<'brand,I>> {
+        self.tracker.ids().into_iter()
+            .map(|id| BrandedId { id, brand: PhantomData })
+            .collect()
+    }
+}
+
+/**
+Run a closure with a [BrandedTracker] view of a tracker, GhostCell-style.
+
+Every call creates a fresh `'brand` lifetime, and the closure's result cannot mention it,
+so [BrandedId]s cannot escape the closure or be passed to a tracker from a different call.
+```
+use ids::{Id32, Identified, Identifier, TypedId};
+use ids::trackers::{DenseIdTracker, with_brand};
+
+struct Names;
+type NameId = TypedId<Names, Id32>;
+let mut tracker = DenseIdTracker::<NameId, Identified<NameId, &str>>::default();
+let name = with_brand(&mut tracker, |mut branded| {
+    let (id, _) = branded.put(Identified::new("Ada"));
+    let element = branded.get(id).unwrap();
+    let name = *element.lock().unwrap().value();
+    name
+});
+assert_eq!(name, "Ada");
+```
+An id cannot be used with the view of another call, even of the same tracker:
+```compile_fail
+use ids::{Id32, Identified, Identifier, TypedId};
+use ids::trackers::{DenseIdTracker, with_brand};
+
+struct Names;
+type NameId = TypedId<Names, Id32>;
+let mut tracker = DenseIdTracker::<NameId, Identified<NameId, &str>>::default();
+let mut other = DenseIdTracker::<NameId, Identified<NameId, &str>>::default();
+with_brand(&mut tracker, |mut branded| {
+    let (id, _) = branded.put(Identified::new("Ada"));
+    with_brand(&mut other, |other| { other.get(id); });
+});
+```
+And an id cannot be returned from the closure:
+```compile_fail
+use ids::{Id32, Identified, Identifier, TypedId};
+use ids::trackers::{DenseIdTracker, with_brand};
+
+struct Names;
+type NameId = TypedId<Names, Id32>;
+let mut tracker = DenseIdTracker::<NameId, Identified<NameId, &str>>::default();
+let id = with_brand(&mut tracker, |mut branded| branded.put(Identified::new("Ada")).0);
+```
+ */
+pub fn with_brand<I, T, Tr, R, F>(tracker: &mut Tr, f: F) -> R
+where I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>, F: for<'brand> FnOnce(BrandedTracker<'_,'brand,I,T,Tr>) -> R {
+    f(BrandedTracker { tracker, p: PhantomData, brand: PhantomData })
+}
+
+/// The next tag to give to a tracker, so that every tracker's tag is unique
+static NEXT_TRACKER_TAG: AtomicU64 = AtomicU64::new(0);
+
+/// Get a tag which has not been given to any other owner of handles, such as a [TaggedTracker], a [super::IndexedIdTracker] or an entity allocator
+pub(crate) fn next_tracker_tag() -> u64 { NEXT_TRACKER_TAG.fetch_add(1, Ordering::Relaxed) }
+
+/**
+An id which remembers which [TaggedTracker] issued it, for when ids must outlive a [with_brand] closure.
+
+In debug builds, the id carries its tracker's tag and using it with another tracker panics.
+In release builds, the tag is removed and this is exactly as large as `I`.
+ */
+pub struct TaggedId<I: Identifier> {
+    id: I,
+    #[cfg(debug_assertions)]
+    tag: u64,
+}
+
+impl <I: Identifier> TaggedId<I> {
+    /// Get the underlying id, discarding the tag
+    pub fn id(self) -> I { self.id }
+}
+
+impl <I: Identifier> Clone for TaggedId<I> {
+    fn clone(&self) -> Self { *self }
+}
+impl <I: Identifier> Copy for TaggedId<I> {}
+impl <I: Identifier> PartialEq for TaggedId<I> {
+    fn eq(&self, other: &Self) -> bool { self.id == other.id }
+}
+impl <I: Identifier> Eq for TaggedId<I> {}
+impl <I: Identifier> Hash for TaggedId<I> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) { self.id.hash(state) }
+}
+impl <I: Identifier> Debug for TaggedId<I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TaggedId").field(&self.id).finish()
+    }
+}
+
+/**
+A tracker which issues [TaggedId]s, and checks in debug builds that it is only given back its own ids.
+ */
+pub struct TaggedTracker<I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> {
+    tracker: Tr,
+    p: PhantomData<(I,T)>,
+    #[cfg(debug_assertions)]
+    tag: u64,
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> TaggedTracker<I,T,Tr> {
+    /// Wrap a tracker, giving it a new unique tag
+    pub fn new(tracker: Tr) -> Self {
+        Self {
+            tracker,
+            p: PhantomData,
+            #[cfg(debug_assertions)]
+            tag: next_tracker_tag(),
+        }
+    }
+
+    /// Unwrap the tracker. Any [TaggedId]s it issued can no longer be checked.
+    pub fn into_inner(self) -> Tr { self.tracker }
+
+    /// Get an element of this tracker, panicking in debug builds if the id was issued by another tracker
+    pub fn get(&self, id: TaggedId<I>) -> Option<Arc<Mutex<T>>> {
+        #[cfg(debug_assertions)]
+        assert_eq!(id.tag, self.tag, "{}", WRONG_TRACKER_ERROR);
+        self.tracker.get(id.id)
+    }
+
+    /// Insert an element into this tracker, returning its tagged id along with the element
+    pub fn put(&mut self, element: T) -> (TaggedId<I>, Arc<Mutex<T>>) {
+        let element = self.tracker.put(element);
+        let id = element.lock().expect(NEW_MUTEX_POISONED_ERROR).get_id();
+        (self.tagged(id), element)
+    }
+
+    /// Get the tagged ids of every element in this tracker, in ascending order
+    pub fn ids(&self) -> Vec<TaggedId<I>> {
+        self.tracker.ids().into_iter()
+            .map(|id| self.tagged(id))
+            .collect()
+    }
+
+    fn tagged(&self, id: I) -> TaggedId<I> {
+        TaggedId {
+            id,
+            #[cfg(debug_assertions)]
+            tag: self.tag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TaggedTracker, with_brand};
+    use crate::Identifier;
+    use crate::trackers::{DenseIdTracker, IdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    #[test]
+    fn branded_views_find_their_own_elements() {
+        let mut tracker = DenseIdTracker::default();
+        let removed = tracker.put(Item::new("removed")).lock().unwrap().id;
+        tracker.remove(removed);
+        let names = with_brand(&mut tracker, |mut branded| {
+            let (a, _) = branded.put(Item::new("a"));
+            let (b, _) = branded.put(Item::new("b"));
+            assert!(branded.brand(removed).is_none());
+            assert_eq!(branded.brand(b.id()), Some(b));
+            assert_eq!(branded.ids(), vec![a, b]);
+            [a, b].map(|id| branded.get(id).unwrap().lock().unwrap().name.clone())
+        });
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(tracker.ids(), vec![ItemId::first().next(), ItemId::first().next().next()]);
+    }
+
+    #[test]
+    fn tagged_trackers_accept_their_own_ids() {
+        let mut tracker = TaggedTracker::new(DenseIdTracker::default());
+        let (id, _) = tracker.put(Item::new("a"));
+        assert_eq!(tracker.get(id).unwrap().lock().unwrap().name, "a");
+        assert_eq!(tracker.ids(), vec![id]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "other than the one which issued it")]
+    fn tagged_trackers_reject_ids_of_other_trackers() {
+        let mut tracker = TaggedTracker::new(DenseIdTracker::<ItemId,Item>::default());
+        let mut other = TaggedTracker::new(DenseIdTracker::<ItemId,Item>::default());
+        let (id, _) = other.put(Item::new("a"));
+        tracker.put(Item::new("b"));
+        tracker.get(id);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::{Mutex, Arc};
+
+use crate::{content_id, Id128, IdentifiedBy, Identifier, UpdatableIdStore};
+use crate::errors::NEW_MUTEX_POISONED_ERROR;
+
+use super::IdTracker;
+
+/**
+A tracker which deduplicates its elements by content.
+
+Each element is given a content id when it is put into the tracker.
+If an element with the same content id and equal content is already present, that element is returned and the new one is discarded.
+Content ids are not cryptographic hashes and can be made to collide, so elements are compared with [PartialEq] before one is reused.
+
+Elements keep the ids given by the wrapped tracker, rather than being identified by their content ids.
+Content should not be changed through the returned mutexes, since the index would not see the change:
+the element would still be found by its old content id, and not by its new one.
+Trackers key their elements by [usize], which cannot hold a 128-bit content id, so content ids are looked up through [ContentIdTracker::get_by_content] instead.
+ */
+pub struct ContentIdTracker<I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> {
+    tracker: Tr,
+    content: Box<dyn Fn(&T) -> Id128 + Send + Sync>,
+    index: HashMap<Id128, Vec<I>>,
+    p: PhantomData<T>,
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> ContentIdTracker<I,T,Tr> {
+    /**
+    Wrap an empty tracker, deriving the content id of each element with a function.
+
+    The function should only consider the content of the element, and not its id.
+     */
+    pub fn new<F: Fn(&T) -> Id128 + Send + Sync + 'static>(tracker: Tr, content: F) -> Self {
+        Self { tracker, content: Box::new(content), index: HashMap::new(), p: PhantomData }
+    }
+
+    /// Get the ids of the elements with some content id. There is at most one, unless different contents have collided.
+    pub fn get_by_content(&self, content: Id128) -> Vec<I> {
+        self.index.get(&content).cloned().unwrap_or_default()
+    }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I> + Hash + 'static, Tr: IdTracker<I,T>> ContentIdTracker<I,T,Tr> {
+    /**
+    Wrap an empty tracker, deriving the content id of each element from its [Hash] implementation.
+
+    The [Hash] implementation should not include the id of the element, since that is changed when it is inserted.
+     */
+    pub fn with_hash(tracker: Tr) -> Self { Self::new(tracker, content_id::<T>) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I> + PartialEq, Tr: IdTracker<I,T>> IdTracker<I,T> for ContentIdTracker<I,T,Tr> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.tracker.get(id) }
+
+    /**
+    Insert an element, unless one with equal content is already present, in which case return that instead.
+
+    The new element is given the id of each candidate before they are compared, so a [PartialEq] implementation which includes the id still works.
+     */
+    fn put(&mut self, mut element: T) -> Arc<Mutex<T>> {
+        let content = (self.content)(&element);
+        for id in self.index.get(&content).into_iter().flatten() {
+            let existing = match self.tracker.get(*id) {
+                Some(existing) => existing,
+                None => continue,
+            };
+            element.set_id(*id);
+            let equal = match existing.lock() {
+                Ok(guard) => *guard == element,
+                Err(_) => false,
+            }; if equal { return existing; }
+        }
+        let element = self.tracker.put(element);
+        let id = element.lock().expect(NEW_MUTEX_POISONED_ERROR).get_id();
+        self.index.entry(content).or_default().push(id);
+        return element;
+    }
+
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> {
+        let element = self.tracker.remove(id);
+        if let Some(element) = &element {
+            match element.lock() {
+                Ok(guard) => {
+                    let content = (self.content)(&guard);
+                    if let Some(ids) = self.index.get_mut(&content) {
+                        ids.retain(|indexed| *indexed != id);
+                        if ids.is_empty() { self.index.remove(&content); }
+                    }
+                }, Err(_) => self.index.retain(|_, ids| {
+                    ids.retain(|indexed| *indexed != id);
+                    !ids.is_empty()
+                }),
+            }
+        }; return element;
+    }
+
+    fn ids(&self) -> Vec<I> { self.tracker.ids() }
+
+    fn flatten(&mut self) -> Result<HashMap<I,I>,()> {
+        let mapping = match self.tracker.flatten() {
+            Ok(mapping) => mapping,
+            Err(err) => return Err(err),
+        }; self.update_ids(&mapping);
+        return Ok(mapping);
+    }
+
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()> {
+        let mapping = match self.tracker.flatten_with(stores_to_update) {
+            Ok(mapping) => mapping,
+            Err(err) => return Err(err),
+        }; self.update_ids(&mapping);
+        return Ok(mapping);
+    }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> UpdatableIdStore<I> for ContentIdTracker<I,T,Tr> {
+    /// Update the content index to new ids, dropping any content whose element is no longer present
+    fn update_ids(&mut self, mapping: &HashMap<I,I>) {
+        self.index = self.index.drain()
+            .map(|(content, ids)| (content, ids.into_iter().filter_map(|id| mapping.get(&id).copied()).collect::<Vec<I>>()))
+            .filter(|(_, ids)| !ids.is_empty())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentIdTracker;
+    use crate::{content_id, Id128, Identifier};
+    use crate::trackers::{DenseIdTracker, IdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    type Tracker = ContentIdTracker<ItemId,Item,DenseIdTracker<ItemId,Item>>;
+
+    fn by_name() -> Tracker {
+        ContentIdTracker::new(DenseIdTracker::default(), |item: &Item| content_id(&item.name))
+    }
+
+    #[test]
+    fn equal_content_is_stored_once() {
+        let mut tracker = by_name();
+        let a = tracker.put(Item::new("a"));
+        tracker.put(Item::new("b"));
+        let again = tracker.put(Item::new("a"));
+        assert!(std::sync::Arc::ptr_eq(&a, &again));
+        assert_eq!(tracker.ids().len(), 2);
+        assert_eq!(tracker.get_by_content(content_id(&"a")), vec![a.lock().unwrap().id]);
+        assert_eq!(tracker.get_by_content(content_id(&"c")), vec![]);
+    }
+
+    #[test]
+    fn colliding_content_ids_are_told_apart_by_equality() {
+        let mut tracker = Tracker::new(DenseIdTracker::default(), |_: &Item| Id128(0));
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        let b = tracker.put(Item::new("b")).lock().unwrap().id;
+        assert_ne!(a, b);
+        assert_eq!(tracker.put(Item::new("b")).lock().unwrap().id, b);
+        assert_eq!(tracker.get_by_content(Id128(0)), vec![a, b]);
+        tracker.remove(a);
+        assert_eq!(tracker.get_by_content(Id128(0)), vec![b]);
+    }
+
+    #[test]
+    fn flattening_moves_the_content_index() {
+        let mut tracker = by_name();
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        let b = tracker.put(Item::new("b")).lock().unwrap().id;
+        tracker.remove(a);
+        let mapping = tracker.flatten().unwrap();
+        assert_eq!(mapping[&b], ItemId::first());
+        assert_eq!(tracker.get_by_content(content_id(&"b")), vec![ItemId::first()]);
+        assert_eq!(tracker.get_by_content(content_id(&"a")), vec![]);
+        // The moved element is still found when equal content is put again
+        assert_eq!(tracker.put(Item::new("b")).lock().unwrap().id, ItemId::first());
+        assert_eq!(tracker.ids(), vec![ItemId::first()]);
+    }
+
+    #[test]
+    fn content_trackers_can_be_sent_between_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&by_name());
+    }
+}
@@ -2,7 +2,7 @@ use std::{sync::{Mutex, Arc}, collections::HashMap};
 
 use crate::{Identifier, IdentifiedBy, intmaps::DenseIntMap, UpdatableIdStore};
 
-use super::{inner::IdTrackerInner, IdTracker};
+use super::{inner::IdTrackerInner, IdTracker, ReservingIdTracker};
 
 pub struct DenseIdTracker<I: Identifier, T: IdentifiedBy<I>> {
     inner: IdTrackerInner<I,T,DenseIntMap<Arc<Mutex<T>>>>
@@ -11,7 +11,76 @@ pub struct DenseIdTracker<I: Identifier, T: IdentifiedBy<I>> {
 impl <I: Identifier, T: IdentifiedBy<I>> IdTracker<I,T> for DenseIdTracker<I,T> {
     fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.get(id) }
     fn put(&mut self, element: T) -> Arc<Mutex<T>> { self.inner.put(element) }
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.remove(id) }
+    fn ids(&self) -> Vec<I> { self.inner.ids() }
     fn flatten(&mut self) -> Result<std::collections::HashMap<I,I>,()> { self.inner.flatten() }
     fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>
         { self.inner.flatten_with(stores_to_update) }
 }
+
+impl <I: Identifier, T: IdentifiedBy<I>> ReservingIdTracker<I,T> for DenseIdTracker<I,T> {
+    fn reserve(&mut self) -> I { self.inner.reserve() }
+    fn fill(&mut self, id: I, element: T) -> Result<Arc<Mutex<T>>,()> { self.inner.fill(id, element) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> Default for DenseIdTracker<I,T> {
+    fn default() -> Self {
+        Self { inner: IdTrackerInner::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DenseIdTracker;
+    use crate::Identifier;
+    use crate::trackers::{IdTracker, ReservingIdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    #[test]
+    fn reserved_ids_are_skipped_until_filled() {
+        let mut tracker = DenseIdTracker::default();
+        let reserved = tracker.reserve();
+        let put = tracker.put(Item::new("put")).lock().unwrap().id;
+        assert_ne!(put, reserved);
+        assert!(tracker.get(reserved).is_none());
+        assert_eq!(tracker.ids(), vec![put]);
+
+        let filled = tracker.fill(reserved, Item::new("filled")).unwrap();
+        assert_eq!(filled.lock().unwrap().id, reserved);
+        assert_eq!(tracker.get(reserved).unwrap().lock().unwrap().name, "filled");
+        assert_eq!(tracker.ids(), vec![reserved, put]);
+    }
+
+    #[test]
+    fn ids_can_only_be_filled_once() {
+        let mut tracker = DenseIdTracker::default();
+        let reserved = tracker.reserve();
+        assert!(tracker.fill(reserved, Item::new("first")).is_ok());
+        assert!(tracker.fill(reserved, Item::new("second")).is_err());
+        assert_eq!(tracker.get(reserved).unwrap().lock().unwrap().name, "first");
+        // Ids which were never reserved cannot be filled either
+        assert!(tracker.fill(reserved.next(), Item::new("unreserved")).is_err());
+    }
+
+    #[test]
+    fn flattening_releases_unfilled_reservations() {
+        let mut tracker = DenseIdTracker::default();
+        let reserved = tracker.reserve();
+        let put = tracker.put(Item::new("put")).lock().unwrap().id;
+        let mapping = tracker.flatten().unwrap();
+        assert_eq!(mapping[&put], ItemId::first());
+        assert!(!mapping.contains_key(&reserved));
+        assert!(tracker.fill(reserved, Item::new("late")).is_err());
+        assert_eq!(tracker.ids(), vec![ItemId::first()]);
+    }
+
+    #[test]
+    fn put_with_gives_elements_their_id_as_they_are_built() {
+        let mut tracker = DenseIdTracker::default();
+        tracker.put(Item::new("first"));
+        let element = tracker.put_with(|id| Item { id, name: format!("built as {:?}", usize::try_from(id).unwrap()) });
+        let id = element.lock().unwrap().id;
+        assert_eq!(id, ItemId::first().next());
+        assert_eq!(tracker.get(id).unwrap().lock().unwrap().name, "built as 1");
+    }
+}
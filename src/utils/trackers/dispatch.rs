@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Mutex, Arc};
+
+use crate::{IdentifiedBy, Identifier, PackedId};
+use crate::errors::CONVERT_TO_USIZE_ERROR;
+
+use super::IdTracker;
+
+/**
+Routes [PackedId]s to the tracker for their kind, for references which may point at one of several kinds of entity.
+
+Each kind is routed to a tracker along with a conversion from that tracker's elements into a common output type,
+such as an enum with one variant per kind.
+ */
+pub struct PackedIdDispatcher<'a, P: PackedId, O> {
+    routes: HashMap<u32, Box<dyn Fn(usize) -> Option<O> + 'a>>,
+    p: PhantomData<P>,
+}
+
+impl <'a, P: PackedId, O> PackedIdDispatcher<'a,P,O> {
+    /**
+    Route ids of a kind to a tracker, converting its elements into the output type.
+
+    Any tracker previously routed for this kind is replaced.
+     */
+    pub fn route<I, T, Tr, F>(&mut self, kind: u32, tracker: &'a Tr, convert: F)
+    where I: Identifier, T: IdentifiedBy<I> + 'a, Tr: IdTracker<I,T>, F: Fn(Arc<Mutex<T>>) -> O + 'a {
+        self.routes.insert(kind, Box::new(move |index| match I::try_from(index) {
+            Ok(id) => tracker.get(id).map(&convert),
+            Err(_) => None,
+        }));
+    }
+
+    /// Get the element which an id refers to, from the tracker routed for its kind
+    pub fn get(&self, id: P) -> Option<O> {
+        match self.routes.get(&id.kind()) {
+            Some(route) => route(id.index()),
+            None => None,
+        }
+    }
+
+    /// Pack a tracker id with a kind, returning an [Err] if either is too large for the packed id
+    pub fn pack<I: Identifier>(kind: u32, id: I) -> Result<P,()> {
+        P::pack(kind, id.try_into().expect(CONVERT_TO_USIZE_ERROR))
+    }
+}
+
+impl <P: PackedId, O> Default for PackedIdDispatcher<'_,P,O> {
+    fn default() -> Self {
+        Self { routes: HashMap::new(), p: PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackedIdDispatcher;
+    use crate::{Identified, IdentifiedBy, Identifier, PackedId32};
+    use crate::trackers::{DenseIdTracker, IdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    const ITEMS: u32 = 0;
+    const NUMBERS: u32 = 1;
+
+    #[derive(PartialEq, Debug)]
+    enum Entity { Item(String), Number(u64) }
+
+    #[test]
+    fn ids_are_routed_to_the_tracker_for_their_kind() {
+        let mut items = DenseIdTracker::<ItemId,Item>::default();
+        let mut numbers = DenseIdTracker::<ItemId,Identified<ItemId,u64>>::default();
+        let item = items.put(Item::new("a")).lock().unwrap().id;
+        numbers.put(Identified::new(10));
+        let number = numbers.put(Identified::new(20)).lock().unwrap().get_id();
+
+        let mut dispatcher = PackedIdDispatcher::<PackedId32<4>,Entity>::default();
+        dispatcher.route(ITEMS, &items, |element| Entity::Item(element.lock().unwrap().name.clone()));
+        dispatcher.route(NUMBERS, &numbers, |element| Entity::Number(*element.lock().unwrap().value()));
+
+        let packed_item = PackedIdDispatcher::<PackedId32<4>,Entity>::pack(ITEMS, item).unwrap();
+        let packed_number = PackedIdDispatcher::<PackedId32<4>,Entity>::pack(NUMBERS, number).unwrap();
+        assert_eq!(packed_item.index(), packed_number.index() - 1);
+        assert_eq!(dispatcher.get(packed_item), Some(Entity::Item("a".to_string())));
+        assert_eq!(dispatcher.get(packed_number), Some(Entity::Number(20)));
+        // An index with no element, and a kind with no route
+        assert_eq!(dispatcher.get(PackedId32::new(ITEMS, 5).unwrap()), None);
+        assert_eq!(dispatcher.get(PackedId32::new(2, 0).unwrap()), None);
+    }
+
+    #[test]
+    fn routing_a_kind_again_replaces_its_tracker() {
+        let mut first = DenseIdTracker::<ItemId,Item>::default();
+        let mut second = DenseIdTracker::<ItemId,Item>::default();
+        first.put(Item::new("first"));
+        second.put(Item::new("second"));
+        let mut dispatcher = PackedIdDispatcher::<PackedId32<4>,String>::default();
+        dispatcher.route(ITEMS, &first, |element| element.lock().unwrap().name.clone());
+        dispatcher.route(ITEMS, &second, |element| element.lock().unwrap().name.clone());
+        let id = PackedIdDispatcher::<PackedId32<4>,String>::pack(ITEMS, ItemId::first()).unwrap();
+        assert_eq!(dispatcher.get(id), Some("second".to_string()));
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Mutex, Arc};
+
+use crate::{Identified, Identifier, UpdatableIdStore};
+
+use super::IdTracker;
+
+/**
+A tracker for values which do not implement [crate::IdentifiedBy], such as foreign types.
+
+Each value is stored in an [Identified] wrapper which keeps its id alongside it,
+so flattening updates the wrapper's id and leaves the value untouched.
+ */
+pub struct IdentifiedTracker<I: Identifier, T, Tr: IdTracker<I,Identified<I,T>>> {
+    tracker: Tr,
+    p: PhantomData<(I,T)>,
+}
+
+impl <I: Identifier, T, Tr: IdTracker<I,Identified<I,T>>> IdentifiedTracker<I,T,Tr> {
+    /// Wrap a tracker of [Identified] values
+    pub fn new(tracker: Tr) -> Self { Self { tracker, p: PhantomData } }
+
+    /// Unwrap the tracker of [Identified] values
+    pub fn into_inner(self) -> Tr { self.tracker }
+
+    /// Insert a value, wrapping it with its new id
+    pub fn put_value(&mut self, value: T) -> Arc<Mutex<Identified<I,T>>> {
+        self.tracker.put(Identified::new(value))
+    }
+
+    /**
+    Run a function on the value with some id, returning its result.
+
+    Returns [None] if there is no value with this id, or an [Err] if its mutex is poisoned.
+     */
+    pub fn with_value<R, F: FnOnce(&T) -> R>(&self, id: I, read: F) -> Result<Option<R>,()> {
+        let element = match self.tracker.get(id) {
+            Some(element) => element,
+            None => return Ok(None),
+        }; let result = match element.lock() {
+            Ok(guard) => Ok(Some(read(guard.value()))),
+            Err(_) => Err(()),
+        }; return result;
+    }
+}
+
+impl <I: Identifier, T: Clone, Tr: IdTracker<I,Identified<I,T>>> IdentifiedTracker<I,T,Tr> {
+    /// Get a copy of the value with some id. Returns [None] if there is no value with this id, or an [Err] if its mutex is poisoned.
+    pub fn get_value(&self, id: I) -> Result<Option<T>,()> {
+        self.with_value(id, T::clone)
+    }
+
+    /**
+    Remove the value with some id, returning it without its wrapper.
+
+    The value is moved out if nothing else still holds its mutex, and copied otherwise.
+    Returns [None] if there is no value with this id, or an [Err] if its mutex is poisoned, in which case it is still removed.
+     */
+    pub fn remove_value(&mut self, id: I) -> Result<Option<T>,()> {
+        let element = match self.tracker.remove(id) {
+            Some(element) => element,
+            None => return Ok(None),
+        }; let result = match Arc::try_unwrap(element) {
+            Ok(mutex) => mutex.into_inner().map(Identified::into_inner).map_err(|_| ()),
+            Err(shared) => shared.lock().map(|guard| guard.value().clone()).map_err(|_| ()),
+        }; return result.map(Some);
+    }
+}
+
+impl <I: Identifier, T, Tr: IdTracker<I,Identified<I,T>>> IdTracker<I,Identified<I,T>> for IdentifiedTracker<I,T,Tr> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<Identified<I,T>>>> { self.tracker.get(id) }
+    fn put(&mut self, element: Identified<I,T>) -> Arc<Mutex<Identified<I,T>>> { self.tracker.put(element) }
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<Identified<I,T>>>> { self.tracker.remove(id) }
+    fn ids(&self) -> Vec<I> { self.tracker.ids() }
+    fn flatten(&mut self) -> Result<HashMap<I,I>,()> { self.tracker.flatten() }
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>
+        { self.tracker.flatten_with(stores_to_update) }
+}
+
+impl <I: Identifier, T, Tr: IdTracker<I,Identified<I,T>> + Default> Default for IdentifiedTracker<I,T,Tr> {
+    fn default() -> Self {
+        Self { tracker: Tr::default(), p: PhantomData }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    use super::IdentifiedTracker;
+    use crate::{Identified, IdentifiedBy, Identifier};
+    use crate::trackers::{ContentIdTracker, DenseIdTracker, IdTracker};
+    use crate::trackers::testing::ItemId;
+
+    type Tracker = IdentifiedTracker<ItemId,String,DenseIdTracker<ItemId,Identified<ItemId,String>>>;
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn wrappers_are_equal_only_with_the_same_id_but_hash_by_value() {
+        let a = Identified::with_id(ItemId::first(), "x");
+        let b = Identified::with_id(ItemId::first().next(), "x");
+        assert_ne!(a, b);
+        assert_eq!(a, Identified::with_id(ItemId::first(), "x"));
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+
+    #[test]
+    fn values_are_read_and_removed_through_their_ids() {
+        let mut tracker = Tracker::default();
+        let a = tracker.put_value("a".to_string()).lock().unwrap().get_id();
+        let b = tracker.put_value("b".to_string()).lock().unwrap().get_id();
+        assert_eq!(tracker.with_value(b, |value| value.len()), Ok(Some(1)));
+        assert_eq!(tracker.get_value(a), Ok(Some("a".to_string())));
+        assert_eq!(tracker.remove_value(a), Ok(Some("a".to_string())));
+        assert_eq!(tracker.get_value(a), Ok(None));
+        assert_eq!(tracker.remove_value(a), Ok(None));
+
+        // A value which is still shared is copied out
+        let shared = tracker.get(b).unwrap();
+        assert_eq!(tracker.remove_value(b), Ok(Some("b".to_string())));
+        assert_eq!(shared.lock().unwrap().value(), "b");
+    }
+
+    #[test]
+    fn flattening_updates_the_wrappers_and_not_the_values() {
+        let mut tracker = Tracker::default();
+        let a = tracker.put_value("a".to_string()).lock().unwrap().get_id();
+        let b = tracker.put_value("b".to_string());
+        tracker.remove(a);
+        tracker.flatten().unwrap();
+        assert_eq!(b.lock().unwrap().get_id(), ItemId::first());
+        assert_eq!(tracker.get_value(ItemId::first()), Ok(Some("b".to_string())));
+    }
+
+    #[test]
+    fn content_trackers_deduplicate_wrapped_values() {
+        let mut tracker = ContentIdTracker::with_hash(DenseIdTracker::<ItemId,Identified<ItemId,String>>::default());
+        let a = tracker.put(Identified::new("a".to_string()));
+        tracker.put(Identified::new("b".to_string()));
+        let again = tracker.put(Identified::new("a".to_string()));
+        assert!(std::sync::Arc::ptr_eq(&a, &again));
+        assert_eq!(tracker.ids().len(), 2);
+    }
+}
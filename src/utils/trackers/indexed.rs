@@ -0,0 +1,308 @@
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, Arc};
+
+use crate::{IdentifiedBy, Identifier, UpdatableIdStore};
+use crate::errors::NEW_MUTEX_POISONED_ERROR;
+
+use super::IdTracker;
+use super::branded::next_tracker_tag;
+
+/**
+A handle to one of the secondary indexes of an [IndexedIdTracker], which remembers the type of its keys.
+
+A handle can only be used with the tracker which created it.
+ */
+pub struct IndexHandle<K> {
+    tracker: u64,
+    position: usize,
+    k: PhantomData<fn() -> K>,
+}
+
+impl <K> Clone for IndexHandle<K> {
+    fn clone(&self) -> Self { *self }
+}
+impl <K> Copy for IndexHandle<K> {}
+
+/// An index whose key type has been erased, so that indexes with different key types can be stored together
+trait SecondaryIndex<I: Identifier, T> {
+    fn insert(&mut self, id: I, element: &T);
+    fn remove(&mut self, id: I, element: &T);
+    /// Remove an id without knowing its element's key, by searching every key
+    fn forget(&mut self, id: I);
+    fn update_ids(&mut self, mapping: &HashMap<I,I>);
+    fn as_any(&self) -> &dyn Any;
+}
+
+struct MultiIndex<I: Identifier, T, K: Hash + Eq> {
+    key: Box<dyn Fn(&T) -> K + Send + Sync>,
+    entries: HashMap<K,HashSet<I>>,
+}
+
+impl <I: Identifier + 'static, T: 'static, K: Hash + Eq + Send + Sync + 'static> SecondaryIndex<I,T> for MultiIndex<I,T,K> {
+    fn insert(&mut self, id: I, element: &T) {
+        self.entries.entry((self.key)(element)).or_default().insert(id);
+    }
+
+    fn remove(&mut self, id: I, element: &T) {
+        let key = (self.key)(element);
+        if let Some(ids) = self.entries.get_mut(&key) {
+            ids.remove(&id);
+            if ids.is_empty() { self.entries.remove(&key); }
+        }
+    }
+
+    fn forget(&mut self, id: I) {
+        self.entries.retain(|_, ids| { ids.remove(&id); !ids.is_empty() });
+    }
+
+    fn update_ids(&mut self, mapping: &HashMap<I,I>) {
+        for ids in self.entries.values_mut() {
+            *ids = ids.iter().filter_map(|id| mapping.get(id).copied()).collect();
+        }; self.entries.retain(|_, ids| !ids.is_empty());
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+/**
+A tracker with non-unique secondary indexes, for finding every element with some value of a field, such as every task with a given status.
+
+Each index takes a key from every element with an extractor, and is kept up to date when elements are inserted, removed,
+or renumbered by flattening. Elements which are changed through their mutexes are not seen by the indexes,
+so changes to indexed fields should be made with [IndexedIdTracker::modify].
+ */
+pub struct IndexedIdTracker<I: Identifier, T: IdentifiedBy<I>, Tr: IdTracker<I,T>> {
+    tracker: Tr,
+    tag: u64,
+    indexes: Vec<Box<dyn SecondaryIndex<I,T> + Send + Sync>>,
+}
+
+impl <I: Identifier + Send + Sync + 'static, T: IdentifiedBy<I> + 'static, Tr: IdTracker<I,T>> IndexedIdTracker<I,T,Tr> {
+    /// Wrap a tracker, with no secondary indexes
+    pub fn new(tracker: Tr) -> Self {
+        Self { tracker, tag: next_tracker_tag(), indexes: Vec::new() }
+    }
+
+    /**
+    Add a secondary index, keyed by what an extractor takes from each element. Elements already in the tracker are indexed immediately.
+
+    If one of the elements' mutexes is poisoned, returns an [Err] and adds no index.
+     */
+    pub fn add_index<K: Hash + Eq + Send + Sync + 'static, F: Fn(&T) -> K + Send + Sync + 'static>(&mut self, key: F) -> Result<IndexHandle<K>,()> {
+        let mut index = MultiIndex { key: Box::new(key), entries: HashMap::new() };
+        for id in self.tracker.ids() {
+            let element = match self.tracker.get(id) {
+                Some(element) => element,
+                None => continue,
+            }; match element.lock() {
+                Ok(guard) => index.insert(id, &guard),
+                Err(_) => return Err(()),
+            };
+        }
+        self.indexes.push(Box::new(index));
+        return Ok(IndexHandle { tracker: self.tag, position: self.indexes.len() - 1, k: PhantomData });
+    }
+
+    /// Get the index which a handle refers to, or [None] if the handle was created by another tracker
+    fn index<K: Hash + Eq + Send + Sync + 'static>(&self, index: IndexHandle<K>) -> Option<&MultiIndex<I,T,K>> {
+        if index.tracker != self.tag
+            { return None }
+        match self.indexes.get(index.position) {
+            Some(found) => found.as_any().downcast_ref::<MultiIndex<I,T,K>>(),
+            None => None,
+        }
+    }
+
+    /// Get the ids of every element with some key in an index, in no particular order. Returns an [Err] if the handle belongs to another tracker.
+    pub fn get_ids_by<K: Hash + Eq + Send + Sync + 'static>(&self, index: IndexHandle<K>, key: &K) -> Result<Vec<I>,()> {
+        let index = match self.index(index) {
+            Some(index) => index,
+            None => return Err(()),
+        };
+        match index.entries.get(key) {
+            Some(ids) => Ok(ids.iter().copied().collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Get every element with some key in an index, in no particular order. Returns an [Err] if the handle belongs to another tracker.
+    pub fn get_by<K: Hash + Eq + Send + Sync + 'static>(&self, index: IndexHandle<K>, key: &K) -> Result<Vec<Arc<Mutex<T>>>,()> {
+        self.get_ids_by(index, key).map(|ids| ids.into_iter()
+            .filter_map(|id| self.tracker.get(id))
+            .collect())
+    }
+
+    /**
+    Change an element, keeping every index up to date with the change.
+
+    Returns the result of the change, or [None] if there is no element with this id.
+    If the element's mutex is poisoned, returns an [Err] without making the change.
+    If the change panics, the element is re-indexed as it was left before the panic continues.
+    The change should not alter the element's id.
+     */
+    pub fn modify<R, F: FnOnce(&mut T) -> R>(&mut self, id: I, change: F) -> Result<Option<R>,()> {
+        let element = match self.tracker.get(id) {
+            Some(element) => element,
+            None => return Ok(None),
+        }; let mut guard = match element.lock() {
+            Ok(guard) => guard,
+            Err(_) => return Err(()),
+        };
+        for index in self.indexes.iter_mut()
+            { index.remove(id, &guard); }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| change(&mut guard)));
+        for index in self.indexes.iter_mut()
+            { index.insert(id, &guard); }
+        match result {
+            Ok(result) => return Ok(Some(result)),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+impl <I: Identifier + Send + Sync + 'static, T: IdentifiedBy<I> + 'static, Tr: IdTracker<I,T>> IdTracker<I,T> for IndexedIdTracker<I,T,Tr> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.tracker.get(id) }
+
+    fn put(&mut self, element: T) -> Arc<Mutex<T>> {
+        let element = self.tracker.put(element);
+        {
+            let guard = element.lock().expect(NEW_MUTEX_POISONED_ERROR);
+            let id = guard.get_id();
+            for index in self.indexes.iter_mut()
+                { index.insert(id, &guard); }
+        }; return element;
+    }
+
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> {
+        let element = self.tracker.remove(id);
+        if let Some(element) = &element {
+            match element.lock() {
+                Ok(guard) => for index in self.indexes.iter_mut() { index.remove(id, &guard) },
+                Err(_) => for index in self.indexes.iter_mut() { index.forget(id) },
+            }
+        }; return element;
+    }
+
+    fn ids(&self) -> Vec<I> { self.tracker.ids() }
+
+    fn flatten(&mut self) -> Result<HashMap<I,I>,()> {
+        let mapping = match self.tracker.flatten() {
+            Ok(mapping) => mapping,
+            Err(err) => return Err(err),
+        }; self.update_ids(&mapping);
+        return Ok(mapping);
+    }
+
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()> {
+        let mapping = match self.tracker.flatten_with(stores_to_update) {
+            Ok(mapping) => mapping,
+            Err(err) => return Err(err),
+        }; self.update_ids(&mapping);
+        return Ok(mapping);
+    }
+}
+
+impl <I: Identifier + Send + Sync + 'static, T: IdentifiedBy<I> + 'static, Tr: IdTracker<I,T>> UpdatableIdStore<I> for IndexedIdTracker<I,T,Tr> {
+    /// Update every index to new ids, dropping any element which is no longer present
+    fn update_ids(&mut self, mapping: &HashMap<I,I>) {
+        for index in self.indexes.iter_mut()
+            { index.update_ids(mapping); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::IndexedIdTracker;
+    use crate::Identifier;
+    use crate::trackers::{DenseIdTracker, IdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    type Tracker = IndexedIdTracker<ItemId,Item,DenseIdTracker<ItemId,Item>>;
+
+    fn sorted(mut ids: Vec<ItemId>) -> Vec<ItemId> {
+        ids.sort_by_key(|id| usize::try_from(*id).unwrap());
+        return ids;
+    }
+
+    #[test]
+    fn indexes_cover_existing_and_new_elements() {
+        let mut tracker = Tracker::new(DenseIdTracker::default());
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        let by_length = tracker.add_index(|item: &Item| item.name.len()).unwrap();
+        let bb = tracker.put(Item::new("bb")).lock().unwrap().id;
+        let c = tracker.put(Item::new("c")).lock().unwrap().id;
+        assert_eq!(sorted(tracker.get_ids_by(by_length, &1).unwrap()), vec![a, c]);
+        assert_eq!(tracker.get_ids_by(by_length, &2).unwrap(), vec![bb]);
+        assert_eq!(tracker.get_by(by_length, &3).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn modify_moves_elements_between_keys() {
+        let mut tracker = Tracker::new(DenseIdTracker::default());
+        let by_length = tracker.add_index(|item: &Item| item.name.len()).unwrap();
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        let result = tracker.modify(a, |item| { item.name.push('a'); item.name.len() });
+        assert_eq!(result, Ok(Some(2)));
+        assert_eq!(tracker.get_ids_by(by_length, &1).unwrap(), vec![]);
+        assert_eq!(tracker.get_ids_by(by_length, &2).unwrap(), vec![a]);
+        assert_eq!(tracker.modify(a.next(), |_| ()), Ok(None));
+    }
+
+    #[test]
+    fn a_panicking_modify_reindexes_the_element() {
+        let mut tracker = Tracker::new(DenseIdTracker::default());
+        let by_length = tracker.add_index(|item: &Item| item.name.len()).unwrap();
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+            tracker.modify(a, |item| { item.name.push_str("bc"); panic!("changed halfway") })
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(tracker.get_ids_by(by_length, &3).unwrap(), vec![a]);
+        assert_eq!(tracker.get_ids_by(by_length, &1).unwrap(), vec![]);
+        // The element's mutex was poisoned by the panic
+        assert_eq!(tracker.modify(a, |_| ()), Err(()));
+    }
+
+    #[test]
+    fn indexes_follow_removal_and_flattening() {
+        let mut tracker = Tracker::new(DenseIdTracker::default());
+        let by_length = tracker.add_index(|item: &Item| item.name.len()).unwrap();
+        let by_name = tracker.add_index(|item: &Item| item.name.clone()).unwrap();
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        tracker.put(Item::new("b"));
+        tracker.put(Item::new("cc"));
+        tracker.remove(a);
+        assert_eq!(tracker.get_ids_by(by_name, &"a".to_string()).unwrap(), vec![]);
+
+        let mapping = tracker.flatten().unwrap();
+        assert_eq!(tracker.get_ids_by(by_name, &"b".to_string()).unwrap(), vec![ItemId::first()]);
+        assert_eq!(sorted(tracker.get_ids_by(by_length, &1).unwrap()), vec![ItemId::first()]);
+        assert_eq!(tracker.get_ids_by(by_length, &2).unwrap(), vec![ItemId::first().next()]);
+        assert_eq!(mapping.len(), 2);
+        assert_eq!(tracker.get_by(by_name, &"cc".to_string()).unwrap()[0].lock().unwrap().id, ItemId::first().next());
+    }
+
+    #[test]
+    fn handles_only_work_with_their_own_tracker() {
+        let mut tracker = Tracker::new(DenseIdTracker::default());
+        let mut other = Tracker::new(DenseIdTracker::default());
+        tracker.add_index(|item: &Item| item.name.len()).unwrap();
+        let foreign = other.add_index(|item: &Item| item.name.len()).unwrap();
+        assert_eq!(tracker.get_ids_by(foreign, &1), Err(()));
+        assert!(tracker.get_by(foreign, &1).is_err());
+    }
+
+    #[test]
+    fn indexed_trackers_can_be_sent_between_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let mut tracker = Tracker::new(DenseIdTracker::default());
+        tracker.add_index(|item: &Item| item.name.clone()).unwrap();
+        assert_send_sync(&tracker);
+    }
+}
@@ -1,20 +1,23 @@
 use std::marker::PhantomData;
 use std::num::TryFromIntError;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Mutex, Arc};
 
 use crate::intmaps::IntMap;
-use crate::{IdentifiedBy, Identifier, UpdatableIdStore};
+use crate::{IdentifiedBy, Identifier, RandomIdentifier, UpdatableIdStore};
+use crate::errors::{CONVERT_FROM_USIZE_ERROR, CONVERT_TO_USIZE_ERROR, NEW_MUTEX_POISONED_ERROR};
 
 use super::IdTracker;
 
 const RETRIEVE_NEW_ELEMENT_ERROR: &str = "Ids: Failed to retrieve an element which had just been inserted";
-const CONVERT_FROM_USIZE_ERROR: &str = "Ids: failed to convert from usize";
-const CONVERT_TO_USIZE_ERROR: &str = "Ids: failed to convert to usize";
+
+/// How many random ids are drawn by [super::SparseIdTracker::put_random] before it gives up
+pub const RANDOM_ID_ATTEMPTS: usize = 64;
 
 pub struct IdTrackerInner<I: Identifier,T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> {
     p: PhantomData<(I,T)>,
-    map: M
+    map: M,
+    reserved: HashSet<usize>,
 }
 
 impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTrackerInner<I,T,M> {
@@ -25,7 +28,7 @@ impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTrackerInne
     The modified object will be returned, if the mutex is unpoisoned.
     Otherwise an [Err] will be returned
      */
-    fn insert<'a>(&mut self, element: Arc<Mutex<T>>) -> Result<Arc<Mutex<T>>,()> {
+    fn insert(&mut self, element: Arc<Mutex<T>>) -> Result<Arc<Mutex<T>>,()> {
         // Get the id to use
         let id = self.map.add(element);
 
@@ -41,6 +44,70 @@ impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTrackerInne
             Err(_) => return Err(())
         }; return Ok(element);
     }
+
+    /**
+    Allocate an id without inserting anything under it yet, so that it can be given to an element later with [IdTrackerInner::fill].
+
+    This allows structures which refer to each other to be built before any of them are inserted.
+    Reserved ids which have not been filled are released when the tracker is flattened.
+     */
+    pub fn reserve(&mut self) -> I {
+        let k = self.map.reserve();
+        self.reserved.insert(k);
+        I::try_from(k).expect(CONVERT_FROM_USIZE_ERROR)
+    }
+
+    /**
+    Insert an element under an id which was returned by [IdTrackerInner::reserve], setting the element's id to it.
+
+    Returns an [Err] if the id is not currently reserved, such as if it has already been filled.
+     */
+    pub fn fill(&mut self, id: I, mut element: T) -> Result<Arc<Mutex<T>>,()> {
+        let k = id.try_into().expect(CONVERT_TO_USIZE_ERROR);
+        if !self.reserved.remove(&k)
+            { return Err(()) }
+        element.set_id(id);
+        let element = Arc::new(Mutex::new(element));
+        self.map.put(k, Some(element.clone()));
+        return Ok(element);
+    }
+
+    pub(super) fn map(&self) -> &M { &self.map }
+
+    /// Create an empty tracker which stores its elements in a given map
+    pub(super) fn with_map(map: M) -> Self { Self { p: PhantomData, map, reserved: HashSet::new() } }
+}
+
+impl <I: RandomIdentifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTrackerInner<I,T,M> {
+    /**
+    Insert an object into the tracker under a random id.
+
+    New ids are drawn until one is found which is neither in use by another element, nor reserved.
+    Returns an [Err] if [RANDOM_ID_ATTEMPTS] ids in a row are all taken, which only becomes likely once most ids of this type are in use,
+    or if a drawn id does not fit in a [usize], as happens for 128-bit ids on 64-bit targets.
+     */
+    pub fn put_random(&mut self, mut element: T) -> Result<Arc<Mutex<T>>,()> {
+        for _ in 0..RANDOM_ID_ATTEMPTS {
+            let id = I::random();
+            let k = match id.try_into() {
+                Ok(k) => k,
+                Err(_) => return Err(()),
+            };
+            if self.map.get(k).is_some() || self.reserved.contains(&k)
+                { continue }
+            // Set the element's id and insert it
+            element.set_id(id);
+            let element = Arc::new(Mutex::new(element));
+            self.map.put(k, Some(element.clone()));
+            return Ok(element);
+        }; return Err(());
+    }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>> + Default> Default for IdTrackerInner<I,T,M> {
+    fn default() -> Self {
+        Self { p: PhantomData, map: M::default(), reserved: HashSet::new() }
+    }
 }
 
 impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTracker<I,T> for IdTrackerInner<I,T,M> {
@@ -50,13 +117,28 @@ impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTracker<I,T
     
     fn put(&mut self, element: T) -> Arc<Mutex<T>> {
         let result = self.insert(Arc::new(Mutex::new(element)));
-        result.expect("Ids: A brand new mutex was poisoned")
+        result.expect(NEW_MUTEX_POISONED_ERROR)
+    }
+
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> {
+        let k = id.try_into().expect(CONVERT_TO_USIZE_ERROR);
+        let element = self.map.get(k);
+        if element.is_some()
+            { self.map.rmv(k); }
+        return element;
+    }
+
+    fn ids(&self) -> Vec<I> {
+        self.map.keys().into_iter()
+            .map(|k| I::try_from(k).expect(CONVERT_FROM_USIZE_ERROR))
+            .collect()
     }
     
     /**
-    Flatten this tracker, collapsing all spaces where elements have been deleted.
+    Flatten this tracker, collapsing all spaces where elements have been deleted, and updating the id of every element.
 
-    If one of the elements' mutexes is poisoned, returns the corresponding [PoisonError]
+    Every moved element is locked to update its id, so this deadlocks if the calling thread holds one of their guards.
+    If one of the elements' mutexes is poisoned, returns an [Err] and leaves the tracker unchanged.
     Otherwise, returns the mappings from old ids to new ones.
      */
     fn flatten(&mut self) -> Result<HashMap<I,I>,()> {
@@ -64,9 +146,29 @@ impl <I: Identifier, T: IdentifiedBy<I>, M: IntMap<Arc<Mutex<T>>>> IdTracker<I,T
             Ok(vals) => vals,
             Err(err) => return Err(err),
         };
+        let id_mapping: HashMap<I,I> = map_to_ids(mapping).expect(CONVERT_FROM_USIZE_ERROR);
+
+        // Lock every moved element before changing any, so that a poisoned mutex leaves the tracker untouched
+        let mut moved = Vec::new();
+        for new_id in id_mapping.values() {
+            let element = match flattened_map.get((*new_id).try_into().expect(CONVERT_TO_USIZE_ERROR)) {
+                Some(elem) => elem,
+                None => panic!("{}",RETRIEVE_NEW_ELEMENT_ERROR),
+            }; moved.push((element, *new_id));
+        }
+        let mut guards = Vec::new();
+        for (element, new_id) in moved.iter() {
+            match element.lock() {
+                Ok(guard) => guards.push((guard, *new_id)),
+                Err(_) => return Err(()),
+            }
+        }
+        // Then give every element its new id
+        for (mut guard, new_id) in guards
+            { guard.set_id(new_id); }
         self.map = flattened_map;
-        let id_mapping = map_to_ids(mapping);
-        return Ok(id_mapping.expect(CONVERT_FROM_USIZE_ERROR));
+        self.reserved.clear();
+        return Ok(id_mapping);
     }
 
     /**
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::{Mutex, Arc};
+
+use crate::{IdentifiedBy, Identifier, UpdatableIdStore};
+use crate::errors::NEW_MUTEX_POISONED_ERROR;
+
+use super::IdTracker;
+
+const DUPLICATE_KEY_ERROR: &str = "Ids: put an element whose key was already in a KeyedIdTracker; use try_put to handle duplicate keys";
+
+/**
+A tracker with a unique secondary index, which finds elements by a natural key such as an email address or an external id.
+
+The key of each element is taken from it by an extractor when it is inserted, and the index is kept up to date
+when elements are removed and when the tracker is flattened.
+Keys should not be changed through the returned mutexes, since the index would not see the change.
+ */
+pub struct KeyedIdTracker<I: Identifier, T: IdentifiedBy<I>, K: Hash + Eq, Tr: IdTracker<I,T>> {
+    tracker: Tr,
+    key: Box<dyn Fn(&T) -> K + Send + Sync>,
+    index: HashMap<K,I>,
+    p: PhantomData<T>,
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, K: Hash + Eq, Tr: IdTracker<I,T>> KeyedIdTracker<I,T,K,Tr> {
+    /// Wrap an empty tracker, indexing its elements by the key which an extractor takes from them
+    pub fn new<F: Fn(&T) -> K + Send + Sync + 'static>(tracker: Tr, key: F) -> Self {
+        Self { tracker, key: Box::new(key), index: HashMap::new(), p: PhantomData }
+    }
+
+    /// Get the id of the element with some key, if one is present
+    pub fn get_id_by_key(&self, key: &K) -> Option<I> { self.index.get(key).copied() }
+
+    /// Get the element with some key, if one is present
+    pub fn get_by_key(&self, key: &K) -> Option<Arc<Mutex<T>>> {
+        match self.index.get(key) {
+            Some(id) => self.tracker.get(*id),
+            None => None,
+        }
+    }
+
+    /**
+    Get the element with some key, or create and insert it if there is none.
+
+    If the created element does not have the key which was looked up, it is not inserted, and is returned as an [Err].
+     */
+    pub fn get_or_insert_with<F: FnOnce() -> T>(&mut self, key: K, create: F) -> Result<Arc<Mutex<T>>,T> {
+        if let Some(existing) = self.get_by_key(&key)
+            { return Ok(existing); }
+        let element = create();
+        if (self.key)(&element) != key
+            { return Err(element); }
+        return Ok(self.insert_unique(key, element));
+    }
+
+    /**
+    Insert an element, unless another element already has its key.
+
+    Returns the inserted element, or the existing element with the same key as an [Err].
+     */
+    pub fn try_put(&mut self, element: T) -> Result<Arc<Mutex<T>>,Arc<Mutex<T>>> {
+        let key = (self.key)(&element);
+        match self.get_by_key(&key) {
+            Some(existing) => Err(existing),
+            None => Ok(self.insert_unique(key, element)),
+        }
+    }
+
+    /// Insert an element whose key is known not to be in the index
+    fn insert_unique(&mut self, key: K, element: T) -> Arc<Mutex<T>> {
+        let element = self.tracker.put(element);
+        let id = element.lock().expect(NEW_MUTEX_POISONED_ERROR).get_id();
+        self.index.insert(key, id);
+        return element;
+    }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, K: Hash + Eq, Tr: IdTracker<I,T>> IdTracker<I,T> for KeyedIdTracker<I,T,K,Tr> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.tracker.get(id) }
+
+    /// Insert an element, panicking if another element already has its key. Use [KeyedIdTracker::try_put] where keys may be duplicated.
+    fn put(&mut self, element: T) -> Arc<Mutex<T>> {
+        match self.try_put(element) {
+            Ok(element) => element,
+            Err(_) => panic!("{}", DUPLICATE_KEY_ERROR),
+        }
+    }
+
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> {
+        let element = self.tracker.remove(id);
+        if let Some(element) = &element {
+            match element.lock() {
+                Ok(guard) => {
+                    let key = (self.key)(&guard);
+                    if self.index.get(&key) == Some(&id)
+                        { self.index.remove(&key); }
+                }, Err(_) => self.index.retain(|_, indexed| *indexed != id),
+            }
+        }; return element;
+    }
+
+    fn ids(&self) -> Vec<I> { self.tracker.ids() }
+
+    fn flatten(&mut self) -> Result<HashMap<I,I>,()> {
+        let mapping = match self.tracker.flatten() {
+            Ok(mapping) => mapping,
+            Err(err) => return Err(err),
+        }; self.update_ids(&mapping);
+        return Ok(mapping);
+    }
+
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()> {
+        let mapping = match self.tracker.flatten_with(stores_to_update) {
+            Ok(mapping) => mapping,
+            Err(err) => return Err(err),
+        }; self.update_ids(&mapping);
+        return Ok(mapping);
+    }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>, K: Hash + Eq, Tr: IdTracker<I,T>> UpdatableIdStore<I> for KeyedIdTracker<I,T,K,Tr> {
+    /// Update the key index to new ids, dropping any key whose element is no longer present
+    fn update_ids(&mut self, mapping: &HashMap<I,I>) {
+        self.index = self.index.drain()
+            .filter_map(|(key, id)| mapping.get(&id).map(|new_id| (key, *new_id)))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyedIdTracker;
+    use crate::Identifier;
+    use crate::trackers::{DenseIdTracker, IdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    type Tracker = KeyedIdTracker<ItemId,Item,String,DenseIdTracker<ItemId,Item>>;
+
+    fn by_name() -> Tracker {
+        KeyedIdTracker::new(DenseIdTracker::default(), |item: &Item| item.name.clone())
+    }
+
+    #[test]
+    fn keys_are_unique() {
+        let mut tracker = by_name();
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        let duplicate = tracker.try_put(Item::new("a"));
+        assert_eq!(duplicate.unwrap_err().lock().unwrap().id, a);
+        assert_eq!(tracker.ids(), vec![a]);
+        assert_eq!(tracker.get_id_by_key(&"a".to_string()), Some(a));
+        assert!(tracker.get_by_key(&"b".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "already in a KeyedIdTracker")]
+    fn putting_a_duplicate_key_panics() {
+        let mut tracker = by_name();
+        tracker.put(Item::new("a"));
+        tracker.put(Item::new("a"));
+    }
+
+    #[test]
+    fn get_or_insert_with_only_creates_missing_keys() {
+        let mut tracker = by_name();
+        let a = tracker.get_or_insert_with("a".to_string(), || Item::new("a")).unwrap();
+        let again = tracker.get_or_insert_with("a".to_string(), || panic!("created an element for a present key")).unwrap();
+        assert!(std::sync::Arc::ptr_eq(&a, &again));
+        let mismatched = tracker.get_or_insert_with("b".to_string(), || Item::new("c"));
+        assert_eq!(mismatched.err().map(|item| item.name), Some("c".to_string()));
+        assert_eq!(tracker.ids().len(), 1);
+    }
+
+    #[test]
+    fn the_index_follows_removal_and_flattening() {
+        let mut tracker = by_name();
+        let a = tracker.put(Item::new("a")).lock().unwrap().id;
+        tracker.put(Item::new("b"));
+        tracker.remove(a);
+        assert_eq!(tracker.get_id_by_key(&"a".to_string()), None);
+        tracker.flatten().unwrap();
+        assert_eq!(tracker.get_id_by_key(&"b".to_string()), Some(ItemId::first()));
+        assert_eq!(tracker.get_by_key(&"b".to_string()).unwrap().lock().unwrap().id, ItemId::first());
+        // A removed key can be used again
+        assert!(tracker.try_put(Item::new("a")).is_ok());
+    }
+
+    #[test]
+    fn keyed_trackers_can_be_sent_between_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&by_name());
+    }
+}
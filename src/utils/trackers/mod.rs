@@ -1,22 +1,54 @@
 mod inner;
 mod dense;
 mod sparse;
+mod sparse_set;
+mod ordered;
+mod adaptive;
+mod branded;
+mod dispatch;
+mod content;
+mod keyed;
+mod indexed;
+mod identified;
+mod registry;
+#[cfg(test)]
+mod testing;
 
 use std::collections::HashMap;
 use std::sync::{Mutex, Arc};
 
+pub use inner::RANDOM_ID_ATTEMPTS;
 pub use dense::DenseIdTracker;
+pub use sparse::SparseIdTracker;
+pub use sparse_set::SparseSetIdTracker;
+pub use ordered::OrderedIdTracker;
+pub use adaptive::AdaptiveIdTracker;
+pub use branded::*;
+pub use dispatch::PackedIdDispatcher;
+pub use content::ContentIdTracker;
+pub use keyed::KeyedIdTracker;
+pub use indexed::{IndexedIdTracker, IndexHandle};
+pub use identified::IdentifiedTracker;
+pub use registry::{IdRegistry, Registered, RegistryFlattening};
 
 use crate::{IdentifiedBy, Identifier, UpdatableIdStore};
 
+const FILL_RESERVED_ERROR: &str = "Ids: Failed to fill an id which had just been reserved";
+
 pub trait IdTracker<I: Identifier,T: IdentifiedBy<I>> {
     
     fn get(&self, id: I) -> Option<Arc<Mutex<T>>>;
     fn put(&mut self, element: T) -> Arc<Mutex<T>>;
+    /// Remove an element from this tracker, returning it if it was present. Its id is not reused until the tracker is flattened.
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>>;
+    /// Get the ids of every element in this tracker, in ascending order
+    fn ids(&self) -> Vec<I>;
     
     /**
-    Flatten this tracker, collapsing all spaces where elements have been deleted.
+    Flatten this tracker, collapsing all spaces where elements have been deleted, and giving every moved element its new id.
 
+    Moved elements are locked to set their ids, so no guard of an element in this tracker may be held while it is flattened,
+    or the flatten will deadlock.
     If one of the elements' mutexes is poisoned, returns the corresponding [PoisonError]
     Otherwise, returns the mappings from old ids to new ones.
      */
@@ -28,3 +60,24 @@ pub trait IdTracker<I: Identifier,T: IdentifiedBy<I>> {
      */
     fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>;
 }
+
+/**
+An [IdTracker] which can hand out ids before the elements which will take them exist,
+such as for elements which need to know their own id when they are constructed.
+ */
+pub trait ReservingIdTracker<I: Identifier, T: IdentifiedBy<I>>: IdTracker<I,T> {
+    /**
+    Allocate an id without inserting anything under it yet, so that it can be given to an element later with [ReservingIdTracker::fill].
+
+    Reserved ids which have not been filled are released when the tracker is flattened.
+     */
+    fn reserve(&mut self) -> I;
+    /// Insert an element under a reserved id, returning an [Err] if the id is not currently reserved
+    fn fill(&mut self, id: I, element: T) -> Result<Arc<Mutex<T>>,()>;
+    /// Insert an element which is constructed knowing its own id
+    fn put_with<F: FnOnce(I) -> T>(&mut self, create: F) -> Arc<Mutex<T>> {
+        let id = self.reserve();
+        let element = create(id);
+        self.fill(id, element).expect(FILL_RESERVED_ERROR)
+    }
+}
@@ -0,0 +1,118 @@
+use std::{sync::{Mutex, Arc}, collections::HashMap, ops::{Bound, RangeBounds}};
+
+use crate::{Identifier, IdentifiedBy, intmaps::OrderedIntMap, UpdatableIdStore};
+use crate::errors::{CONVERT_FROM_USIZE_ERROR, CONVERT_TO_USIZE_ERROR};
+
+use super::{inner::IdTrackerInner, IdTracker, ReservingIdTracker};
+
+/**
+An [IdTracker] which keeps its ids in order, allowing range queries and lookups of neighbouring elements.
+ */
+pub struct OrderedIdTracker<I: Identifier, T: IdentifiedBy<I>> {
+    inner: IdTrackerInner<I,T,OrderedIntMap<Arc<Mutex<T>>>>
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> OrderedIdTracker<I,T> {
+
+    /// Get every element whose id is within a range, in ascending order of id
+    pub fn range<R: RangeBounds<I>>(&self, range: R) -> Vec<(I, Arc<Mutex<T>>)> {
+        let bounds = (to_key_bound(range.start_bound()), to_key_bound(range.end_bound()));
+        self.inner.map().range(bounds)
+            .map(|(k,v)| (from_key(k), v.clone()))
+            .collect()
+    }
+
+    /// Get the element with the smallest id
+    pub fn first(&self) -> Option<(I, Arc<Mutex<T>>)> { with_id(self.inner.map().first()) }
+    /// Get the element with the largest id
+    pub fn last(&self) -> Option<(I, Arc<Mutex<T>>)> { with_id(self.inner.map().last()) }
+    /// Get the element with the largest id which is smaller than the one given, whether or not the given id is in use
+    pub fn predecessor(&self, id: I) -> Option<(I, Arc<Mutex<T>>)> { with_id(self.inner.map().predecessor(to_key(id))) }
+    /// Get the element with the smallest id which is larger than the one given, whether or not the given id is in use
+    pub fn successor(&self, id: I) -> Option<(I, Arc<Mutex<T>>)> { with_id(self.inner.map().successor(to_key(id))) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> IdTracker<I,T> for OrderedIdTracker<I,T> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.get(id) }
+    fn put(&mut self, element: T) -> Arc<Mutex<T>> { self.inner.put(element) }
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.remove(id) }
+    fn ids(&self) -> Vec<I> { self.inner.ids() }
+    fn flatten(&mut self) -> Result<std::collections::HashMap<I,I>,()> { self.inner.flatten() }
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>
+        { self.inner.flatten_with(stores_to_update) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> ReservingIdTracker<I,T> for OrderedIdTracker<I,T> {
+    fn reserve(&mut self) -> I { self.inner.reserve() }
+    fn fill(&mut self, id: I, element: T) -> Result<Arc<Mutex<T>>,()> { self.inner.fill(id, element) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> Default for OrderedIdTracker<I,T> {
+    fn default() -> Self {
+        Self { inner: IdTrackerInner::default() }
+    }
+}
+
+fn to_key<I: Identifier>(id: I) -> usize { id.try_into().expect(CONVERT_TO_USIZE_ERROR) }
+fn from_key<I: Identifier>(k: usize) -> I { I::try_from(k).expect(CONVERT_FROM_USIZE_ERROR) }
+
+fn to_key_bound<I: Identifier>(bound: Bound<&I>) -> Bound<usize> {
+    match bound {
+        Bound::Included(id) => Bound::Included(to_key(*id)),
+        Bound::Excluded(id) => Bound::Excluded(to_key(*id)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+fn with_id<I: Identifier, T>(entry: Option<(usize, &Arc<Mutex<T>>)>) -> Option<(I, Arc<Mutex<T>>)> {
+    entry.map(|(k,v)| (from_key(k), v.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::OrderedIdTracker;
+    use crate::Identifier;
+    use crate::trackers::IdTracker;
+    use crate::trackers::testing::{Item, ItemId};
+
+    fn id(n: usize) -> ItemId { ItemId::try_from(n).unwrap() }
+
+    fn names(entries: Vec<(ItemId, Arc<Mutex<Item>>)>) -> Vec<(ItemId, String)> {
+        entries.into_iter().map(|(id, item)| (id, item.lock().unwrap().name.clone())).collect()
+    }
+
+    /// A tracker holding "a" to "e" under ids 0 to 4, with "b" and "d" removed
+    fn tracker() -> OrderedIdTracker<ItemId,Item> {
+        let mut tracker = OrderedIdTracker::default();
+        for name in ["a", "b", "c", "d", "e"]
+            { tracker.put(Item::new(name)); }
+        tracker.remove(id(1));
+        tracker.remove(id(3));
+        return tracker;
+    }
+
+    #[test]
+    fn ranges_skip_removed_ids() {
+        let tracker = tracker();
+        assert_eq!(names(tracker.range(id(1)..=id(4))), vec![(id(2), "c".to_string()), (id(4), "e".to_string())]);
+        assert_eq!(names(tracker.range(..id(2))), vec![(ItemId::first(), "a".to_string())]);
+        assert_eq!(names(tracker.range(id(5)..)), vec![]);
+        assert_eq!(tracker.first().unwrap().1.lock().unwrap().name, "a");
+        assert_eq!(tracker.last().unwrap().0, id(4));
+    }
+
+    #[test]
+    fn neighbours_are_found_whether_or_not_the_id_is_in_use() {
+        let tracker = tracker();
+        assert_eq!(tracker.predecessor(id(2)).unwrap().0, id(0));
+        assert_eq!(tracker.predecessor(id(3)).unwrap().0, id(2));
+        assert!(tracker.predecessor(id(0)).is_none());
+        assert_eq!(tracker.successor(id(1)).unwrap().1.lock().unwrap().name, "c");
+        assert_eq!(tracker.successor(id(2)).unwrap().0, id(4));
+        assert!(tracker.successor(id(4)).is_none());
+        let empty = OrderedIdTracker::<ItemId,Item>::default();
+        assert!(empty.first().is_none() && empty.last().is_none() && empty.successor(ItemId::first()).is_none());
+    }
+}
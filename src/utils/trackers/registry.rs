@@ -0,0 +1,217 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Mutex, Arc};
+
+use crate::{IdentifiedBy, Identifier};
+
+use super::IdTracker;
+
+const TRACKER_TYPE_ERROR: &str = "Ids: An IdRegistry tracker did not match the type it was registered under";
+
+/**
+An element type which can be stored in an [IdRegistry], naming the [Identifier] which identifies it
+and the kind of tracker which the registry keeps it in, such as a [super::DenseIdTracker].
+ */
+pub trait Registered: IdentifiedBy<Self::Id> + Sized + 'static {
+    type Id: Identifier + 'static;
+    type Tracker: IdTracker<Self::Id,Self> + Default + 'static;
+}
+
+/// A tracker whose element type has been erased, so that trackers of different types can be stored together
+trait ErasedTracker {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Flatten the tracker, returning its mapping from old ids to new ones as a [Box<dyn Any>]
+    fn flatten_erased(&mut self) -> Result<Box<dyn Any>,()>;
+}
+
+/// The tracker of a registered type, wrapped so that it can be erased without knowing which kind of tracker it is
+struct Erased<T: Registered>(T::Tracker);
+
+impl <T: Registered> ErasedTracker for Erased<T> {
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+    fn flatten_erased(&mut self) -> Result<Box<dyn Any>,()> {
+        match self.0.flatten() {
+            Ok(mapping) => Ok(Box::new(mapping)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/**
+Owns one tracker for each of many element types, so that they do not need to be created and wired up separately.
+
+A type's tracker is created the first time an element of that type is put into the registry.
+ */
+#[derive(Default)]
+pub struct IdRegistry {
+    trackers: HashMap<TypeId, Box<dyn ErasedTracker>>,
+}
+
+impl IdRegistry {
+    /// Get the tracker for an element type, if any elements of that type have been put into the registry
+    pub fn tracker<T: Registered>(&self) -> Option<&T::Tracker> {
+        self.trackers.get(&TypeId::of::<T>())
+            .map(|tracker| &tracker.as_any().downcast_ref::<Erased<T>>().expect(TRACKER_TYPE_ERROR).0)
+    }
+
+    /// Get the tracker for an element type, creating it if it does not exist yet
+    pub fn tracker_mut<T: Registered>(&mut self) -> &mut T::Tracker {
+        let tracker = self.trackers.entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Erased::<T>(T::Tracker::default())));
+        &mut tracker.as_any_mut().downcast_mut::<Erased<T>>().expect(TRACKER_TYPE_ERROR).0
+    }
+
+    /// Get an element by its type and id, if it is present
+    pub fn get<T: Registered>(&self, id: T::Id) -> Option<Arc<Mutex<T>>> {
+        match self.tracker::<T>() {
+            Some(tracker) => tracker.get(id),
+            None => None,
+        }
+    }
+
+    /// Insert an element into the tracker for its type, creating that tracker if this is the first element of the type
+    pub fn put<T: Registered>(&mut self, element: T) -> Arc<Mutex<T>> {
+        self.tracker_mut::<T>().put(element)
+    }
+
+    /// Remove an element by its type and id, returning it if it was present. No tracker is created for a type which has none.
+    pub fn remove<T: Registered>(&mut self, id: T::Id) -> Option<Arc<Mutex<T>>> {
+        match self.trackers.get_mut(&TypeId::of::<T>()) {
+            Some(tracker) => tracker.as_any_mut()
+                .downcast_mut::<Erased<T>>().expect(TRACKER_TYPE_ERROR)
+                .0.remove(id),
+            None => None,
+        }
+    }
+
+    /// Get the ids of every element of a type, in ascending order
+    pub fn ids<T: Registered>(&self) -> Vec<T::Id> {
+        match self.tracker::<T>() {
+            Some(tracker) => tracker.ids(),
+            None => Vec::new(),
+        }
+    }
+
+    /**
+    Flatten the tracker of every element type, returning the mappings from old ids to new ones for each type.
+
+    Every tracker is flattened, even if another could not be, so which trackers are flattened never depends on their order.
+    If one of the elements' mutexes is poisoned, its tracker is left unchanged and the mappings of the others are returned as an [Err].
+     */
+    pub fn flatten_all(&mut self) -> Result<RegistryFlattening,RegistryFlattening> {
+        let mut mappings = HashMap::new();
+        let mut failed = false;
+        for (type_id, tracker) in self.trackers.iter_mut() {
+            match tracker.flatten_erased() {
+                Ok(mapping) => { mappings.insert(*type_id, mapping); },
+                Err(()) => failed = true,
+            }
+        }
+        let flattening = RegistryFlattening { mappings };
+        if failed
+            { return Err(flattening) }
+        return Ok(flattening);
+    }
+}
+
+/**
+The mappings from old ids to new ones for every element type, as returned by [IdRegistry::flatten_all].
+ */
+pub struct RegistryFlattening {
+    mappings: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl RegistryFlattening {
+    /// Get the mapping from old ids to new ones for an element type, if the registry had a tracker for it which could be flattened
+    pub fn get<T: Registered>(&self) -> Option<&HashMap<T::Id,T::Id>> {
+        self.mappings.get(&TypeId::of::<T>())
+            .map(|mapping| mapping.downcast_ref().expect(TRACKER_TYPE_ERROR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::{IdRegistry, Registered};
+    use crate::{Identified, IdentifiedBy, Identifier};
+    use crate::trackers::{DenseIdTracker, OrderedIdTracker};
+    use crate::trackers::testing::{Item, ItemId};
+
+    impl Registered for Item {
+        type Id = ItemId;
+        type Tracker = DenseIdTracker<ItemId,Item>;
+    }
+
+    type Number = Identified<ItemId,u64>;
+    impl Registered for Number {
+        type Id = ItemId;
+        type Tracker = OrderedIdTracker<ItemId,Number>;
+    }
+
+    #[test]
+    fn each_type_has_its_own_tracker() {
+        let mut registry = IdRegistry::default();
+        let item = registry.put(Item::new("a")).lock().unwrap().id;
+        let number = registry.put(Number::new(7)).lock().unwrap().get_id();
+        assert_eq!(item, number);
+        assert_eq!(registry.get::<Item>(item).unwrap().lock().unwrap().name, "a");
+        assert_eq!(*registry.get::<Number>(number).unwrap().lock().unwrap().value(), 7);
+        // The tracker is the one named by the type
+        let _: &OrderedIdTracker<ItemId,Number> = registry.tracker::<Number>().unwrap();
+
+        assert!(registry.remove::<Item>(item).is_some());
+        assert!(registry.get::<Item>(item).is_none());
+        assert_eq!(registry.ids::<Number>(), vec![number]);
+    }
+
+    #[test]
+    fn removing_does_not_create_trackers() {
+        let mut registry = IdRegistry::default();
+        assert!(registry.remove::<Item>(ItemId::first()).is_none());
+        assert!(registry.tracker::<Item>().is_none());
+        assert_eq!(registry.ids::<Item>(), vec![]);
+    }
+
+    #[test]
+    fn flattening_covers_every_type() {
+        let mut registry = IdRegistry::default();
+        let a = registry.put(Item::new("a")).lock().unwrap().id;
+        let b = registry.put(Item::new("b")).lock().unwrap().id;
+        let seven = registry.put(Number::new(7)).lock().unwrap().get_id();
+        let eight = registry.put(Number::new(8)).lock().unwrap().get_id();
+        registry.remove::<Item>(a);
+        registry.remove::<Number>(seven);
+        let flattening = registry.flatten_all().ok().unwrap();
+        assert_eq!(flattening.get::<Item>().unwrap()[&b], ItemId::first());
+        assert_eq!(flattening.get::<Number>().unwrap()[&eight], ItemId::first());
+        assert_eq!(registry.ids::<Item>(), vec![ItemId::first()]);
+        assert_eq!(*registry.get::<Number>(ItemId::first()).unwrap().lock().unwrap().value(), 8);
+    }
+
+    #[test]
+    fn a_poisoned_tracker_does_not_stop_the_others_from_flattening() {
+        let mut registry = IdRegistry::default();
+        let a = registry.put(Item::new("a")).lock().unwrap().id;
+        let b = registry.put(Item::new("b"));
+        let seven = registry.put(Number::new(7)).lock().unwrap().get_id();
+        let eight = registry.put(Number::new(8)).lock().unwrap().get_id();
+        registry.remove::<Item>(a);
+        registry.remove::<Number>(seven);
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = b.lock().unwrap();
+            panic!("poison the element");
+        }));
+
+        let flattening = match registry.flatten_all() {
+            Ok(_) => panic!("flattened a tracker with a poisoned element"),
+            Err(flattening) => flattening,
+        };
+        assert!(flattening.get::<Item>().is_none());
+        assert_eq!(flattening.get::<Number>().unwrap()[&eight], ItemId::first());
+        // The poisoned tracker is left as it was
+        assert_eq!(registry.ids::<Item>(), vec![a.next()]);
+    }
+}
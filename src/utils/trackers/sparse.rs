@@ -0,0 +1,44 @@
+use std::{sync::{Mutex, Arc}, collections::HashMap};
+
+use crate::{Identifier, IdentifiedBy, RandomIdentifier, intmaps::SparseIntMap, UpdatableIdStore};
+
+use super::{inner::IdTrackerInner, IdTracker, ReservingIdTracker};
+
+pub struct SparseIdTracker<I: Identifier, T: IdentifiedBy<I>> {
+    inner: IdTrackerInner<I,T,SparseIntMap<Arc<Mutex<T>>>>
+}
+
+impl <I: RandomIdentifier, T: IdentifiedBy<I>> SparseIdTracker<I,T> {
+    /**
+    Insert an element into this tracker under a random, unguessable id.
+
+    If the drawn id collides with an existing element, another is drawn, up to [crate::trackers::RANDOM_ID_ATTEMPTS] times before an [Err] is returned.
+    Ids which do not fit in a [usize] cannot be tracked, so 128-bit ids always return an [Err] on 64-bit targets.
+
+    Random ids only stay unguessable while every element is inserted this way, and the tracker is never flattened.
+    [IdTracker::put] gives the id after the largest one in use, and [IdTracker::flatten] renumbers every element from zero,
+    so either makes the ids of this tracker enumerable again.
+     */
+    pub fn put_random(&mut self, element: T) -> Result<Arc<Mutex<T>>,()> { self.inner.put_random(element) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> IdTracker<I,T> for SparseIdTracker<I,T> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.get(id) }
+    fn put(&mut self, element: T) -> Arc<Mutex<T>> { self.inner.put(element) }
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.remove(id) }
+    fn ids(&self) -> Vec<I> { self.inner.ids() }
+    fn flatten(&mut self) -> Result<std::collections::HashMap<I,I>,()> { self.inner.flatten() }
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>
+        { self.inner.flatten_with(stores_to_update) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> ReservingIdTracker<I,T> for SparseIdTracker<I,T> {
+    fn reserve(&mut self) -> I { self.inner.reserve() }
+    fn fill(&mut self, id: I, element: T) -> Result<Arc<Mutex<T>>,()> { self.inner.fill(id, element) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> Default for SparseIdTracker<I,T> {
+    fn default() -> Self {
+        Self { inner: IdTrackerInner::default() }
+    }
+}
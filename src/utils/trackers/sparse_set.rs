@@ -0,0 +1,71 @@
+use std::{sync::{Mutex, Arc}, collections::HashMap};
+
+use crate::{Identifier, IdentifiedBy, intmaps::SparseSetIntMap, UpdatableIdStore};
+
+use super::{inner::IdTrackerInner, IdTracker, ReservingIdTracker};
+
+/**
+An [IdTracker] whose elements are stored contiguously, so that iterating over them never visits removed ids.
+ */
+pub struct SparseSetIdTracker<I: Identifier, T: IdentifiedBy<I>> {
+    inner: IdTrackerInner<I,T,SparseSetIntMap<Arc<Mutex<T>>>>
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> SparseSetIdTracker<I,T> {
+    /// Iterate over every element in this tracker, in no particular order
+    pub fn elements(&self) -> impl Iterator<Item = &Arc<Mutex<T>>> { self.inner.map().values().iter() }
+    pub fn len(&self) -> usize { self.inner.map().len() }
+    pub fn is_empty(&self) -> bool { self.inner.map().is_empty() }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> IdTracker<I,T> for SparseSetIdTracker<I,T> {
+    fn get(&self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.get(id) }
+    fn put(&mut self, element: T) -> Arc<Mutex<T>> { self.inner.put(element) }
+    fn remove(&mut self, id: I) -> Option<Arc<Mutex<T>>> { self.inner.remove(id) }
+    fn ids(&self) -> Vec<I> { self.inner.ids() }
+    fn flatten(&mut self) -> Result<std::collections::HashMap<I,I>,()> { self.inner.flatten() }
+    fn flatten_with<Itr: Iterator<Item = Box<dyn UpdatableIdStore<I>>>>(&mut self, stores_to_update: Itr) -> Result<HashMap<I,I>,()>
+        { self.inner.flatten_with(stores_to_update) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> ReservingIdTracker<I,T> for SparseSetIdTracker<I,T> {
+    fn reserve(&mut self) -> I { self.inner.reserve() }
+    fn fill(&mut self, id: I, element: T) -> Result<Arc<Mutex<T>>,()> { self.inner.fill(id, element) }
+}
+
+impl <I: Identifier, T: IdentifiedBy<I>> Default for SparseSetIdTracker<I,T> {
+    fn default() -> Self {
+        Self { inner: IdTrackerInner::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseSetIdTracker;
+    use crate::Identifier;
+    use crate::trackers::IdTracker;
+    use crate::trackers::testing::{Item, ItemId};
+
+    fn names(tracker: &SparseSetIdTracker<ItemId,Item>) -> Vec<String> {
+        let mut names: Vec<_> = tracker.elements().map(|element| element.lock().unwrap().name.clone()).collect();
+        names.sort();
+        return names;
+    }
+
+    #[test]
+    fn elements_skip_removed_ids_and_follow_flattening() {
+        let mut tracker = SparseSetIdTracker::default();
+        let ids: Vec<ItemId> = ["a", "b", "c"].into_iter()
+            .map(|name| tracker.put(Item::new(name)).lock().unwrap().id)
+            .collect();
+        assert!(tracker.remove(ids[0]).is_some());
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(names(&tracker), vec!["b", "c"]);
+        assert_eq!(tracker.get(ids[2]).unwrap().lock().unwrap().name, "c");
+
+        let mapping = tracker.flatten().unwrap();
+        assert_eq!(tracker.ids(), vec![ItemId::first(), ItemId::first().next()]);
+        assert_eq!(tracker.get(mapping[&ids[2]]).unwrap().lock().unwrap().id, mapping[&ids[2]]);
+        assert_eq!(names(&tracker), vec!["b", "c"]);
+    }
+}
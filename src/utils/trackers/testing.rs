@@ -0,0 +1,25 @@
+/*!
+Elements shared by the tests of the trackers.
+ */
+
+use crate::{Id32, IdentifiedBy, Identifier, TypedId};
+
+pub(super) struct Marker;
+pub(super) type ItemId = TypedId<Marker, Id32>;
+
+/// A named element, so that tests can tell elements apart after their ids change
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub(super) struct Item {
+    pub id: ItemId,
+    pub name: String,
+}
+
+impl Item {
+    /// Create an item with a placeholder id, which a tracker replaces when it is inserted
+    pub fn new(name: &str) -> Self { Self { id: ItemId::first(), name: name.to_string() } }
+}
+
+impl IdentifiedBy<ItemId> for Item {
+    fn get_id(&self) -> ItemId { self.id }
+    fn set_id(&mut self, id: ItemId) { self.id = id }
+}